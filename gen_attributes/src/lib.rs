@@ -1,38 +1,74 @@
 extern crate proc_macro;
 
-use gen_attributes_utils::generate_impl_block;
+use gen_attributes_utils::{
+    generate_impl_block, generate_value_accessors, transparent_field, DocArgs, InterfaceArgs,
+};
 use proc_macro::TokenStream;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{NestedMeta, Token};
 
 #[proc_macro_attribute]
 pub fn generate_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = Punctuated::<NestedMeta, Token![,]>::parse_terminated
+        .parse(attr)
+        .and_then(|args| InterfaceArgs::parse(&args))
+        .unwrap_or_else(|err| panic!("{}", err));
+    //ignored items may be trait methods without a body which aren't items on their own
+    if args.ignore {
+        return item;
+    }
     let item = syn::parse(item).unwrap();
     let mut is_func = false;
 
     match item {
-        syn::Item::Fn(ref fun) => {
+        syn::Item::Fn(_) => {
             //general function
             is_func = true;
-            //generics not supported
-            let gene = &fun.sig.generics;
-            assert!(gene.gt_token.is_none(), "Generics not yet supported");
-            assert!(gene.lt_token.is_none(), "Generics not yet supported")
         }
         syn::Item::Enum(_) => {}
         syn::Item::Trait(_) => {}
+        //associated constants
+        syn::Item::Const(_) => {}
+        syn::Item::Struct(ref val) if args.value => {
+            assert!(
+                matches!(val.fields, syn::Fields::Named(_)) && val.generics.params.is_empty(),
+                "value structs need named fields and no type parameters"
+            );
+            let (impl_block, _) = generate_value_accessors(val);
+            let fin = quote::quote! {
+                #item
+
+                #impl_block
+            };
+            return fin.into();
+        }
+        syn::Item::Struct(ref val) if args.transparent => {
+            if let Err(err) = transparent_field(val) {
+                panic!("{}", err)
+            }
+            return quote::quote! { #item }.into();
+        }
         syn::Item::Struct(_) => panic!(
             "Annotate methods of this struct instead. \
         To use enable doc comments on this struct use #[generate_interface_doc] macro instead."
         ),
         _ => panic!("unsuppoted type"),
     }
-    let attr = attr.to_string();
-    if !attr.is_empty() {
-        assert_eq!(
-            attr, "constructor",
-            "only constructor attributes are supported for now"
+    if args.value || args.transparent {
+        panic!("call value or transparent on a struct")
+    }
+    if (args.constructor || args.is_static) && !is_func {
+        panic!("call constructor or static on function")
+    }
+    if let syn::Item::Fn(ref fun) = item {
+        assert!(
+            !args.is_static || fun.sig.receiver().is_none(),
+            "static methods can't take a self receiver"
         );
-        if !is_func {
-            panic!("call constructor on function")
+        //generics are only supported with a concrete type to use
+        if let Err(err) = args.check_signature(&fun.sig) {
+            panic!("{}", err)
         }
     }
     let y = quote::quote! {
@@ -43,12 +79,26 @@ pub fn generate_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 
 #[proc_macro_attribute]
 pub fn generate_interface_doc(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = Punctuated::<NestedMeta, Token![,]>::parse_terminated
+        .parse(attr)
+        .and_then(|args| DocArgs::parse(&args))
+        .unwrap_or_else(|err| panic!("{}", err));
     let item = syn::parse(item).unwrap();
     match item {
-        syn::Item::Struct(_) => {}
-        _ => panic!("Use this macro on only struct`"),
+        //generic structs are written with the types they're instantiated with
+        syn::Item::Struct(ref val) => {
+            if let Err(err) = args.check_instantiations(&val.ident, &val.generics) {
+                panic!("{}", err)
+            }
+        }
+        syn::Item::Enum(_) | syn::Item::Trait(_) => {
+            assert!(
+                args.instantiate.is_empty(),
+                "only structs can be instantiated"
+            )
+        }
+        _ => panic!("Use this macro on only struct, enum or trait`"),
     }
-    assert!(attr.is_empty(), "No attributes allowed yet");
     let fin = quote::quote! {
         #item
     };
@@ -1,14 +1,462 @@
 use itertools::MultiUnzip;
 use quote::format_ident;
-use syn::ItemImpl;
+use syn::{GenericParam, ItemImpl, Lit, Meta, MetaList, NestedMeta};
+
+/// The arguments of `#[generate_interface(...)]`
+#[derive(Debug, Default)]
+pub struct InterfaceArgs {
+    /// `#[generate_interface(constructor)]`
+    pub constructor: bool,
+    /// `#[generate_interface(static)]`
+    pub is_static: bool,
+    /// `#[generate_interface(generic(T = "String"))]`. The type each generic parameter is
+    /// replaced with in the interface file
+    pub generic: Vec<(String, syn::Type)>,
+    /// `#[generate_interface(name = "toString")]`. The name of the method in the foreign language,
+    /// or of the callback on a trait
+    pub name: Option<String>,
+    /// `#[generate_interface(skip_arg = "ctx")]`, may be repeated. Parameters left out of the
+    /// interface since they're supplied on the rust side
+    pub skip_args: Vec<String>,
+    /// `#[generate_interface(ignore)]`. Leaves a method of an annotated trait, or an impl
+    /// method, out of the interface
+    pub ignore: bool,
+    /// `#[generate_interface(value)]`. Exposes a struct as a value class with a getter and
+    /// setter for each of its `pub` fields
+    pub value: bool,
+    /// `#[generate_interface(transparent)]`. Writes a single field tuple struct ie
+    /// `struct Meters(f64);` as its field wherever it's in a signature
+    pub transparent: bool,
+    /// `#[generate_interface(only = "java")]`, may be repeated. The languages the method is
+    /// generated for, every language when empty
+    pub only: Vec<String>,
+}
+
+/// The languages `only` accepts
+const LANGUAGES: [&str; 4] = ["java", "kotlin", "cpp", "python"];
+
+impl InterfaceArgs {
+    /// Parses the comma separated arguments of the attribute
+    pub fn parse<'a>(args: impl IntoIterator<Item = &'a NestedMeta>) -> syn::Result<InterfaceArgs> {
+        let mut result = InterfaceArgs::default();
+        for arg in args {
+            match arg {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("constructor") => {
+                    result.constructor = true
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("static") => {
+                    result.is_static = true
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("ignore") => {
+                    result.ignore = true
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("value") => result.value = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("transparent") => {
+                    result.transparent = true
+                }
+                NestedMeta::Meta(Meta::NameValue(val)) if val.path.is_ident("name") => {
+                    match &val.lit {
+                        Lit::Str(name) if syn::parse_str::<syn::Ident>(&name.value()).is_ok() => {
+                            result.name = Some(name.value())
+                        }
+                        lit => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected an identifier in quotes",
+                            ))
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(val)) if val.path.is_ident("skip_arg") => {
+                    match &val.lit {
+                        Lit::Str(name) if syn::parse_str::<syn::Ident>(&name.value()).is_ok() => {
+                            result.skip_args.push(name.value())
+                        }
+                        lit => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected a parameter name in quotes",
+                            ))
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(val)) if val.path.is_ident("only") => {
+                    match &val.lit {
+                        Lit::Str(name) if LANGUAGES.contains(&name.value().as_str()) => {
+                            result.only.push(name.value())
+                        }
+                        lit => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                format!("expected one of {} in quotes", LANGUAGES.join(", ")),
+                            ))
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("generic") => {
+                    result.generic.extend(parse_type_hints(list)?)
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        "unsupported generate_interface argument",
+                    ))
+                }
+            }
+        }
+        if result.constructor && result.is_static {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "a method can't be both a constructor and static",
+            ));
+        }
+        let other_args = result.constructor
+            || result.is_static
+            || result.name.is_some()
+            || !result.generic.is_empty()
+            || !result.skip_args.is_empty()
+            || !result.only.is_empty();
+        if result.ignore && other_args {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "ignore can't be combined with other arguments",
+            ));
+        }
+        if result.value && (other_args || result.ignore || result.transparent) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "value can't be combined with other arguments",
+            ));
+        }
+        if result.transparent && (other_args || result.ignore) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "transparent can't be combined with other arguments",
+            ));
+        }
+        if result.constructor && result.name.is_some() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "constructors can't be renamed",
+            ));
+        }
+        Ok(result)
+    }
+
+    /// Checks every type parameter of `sig` has a `generic(...)` hint and every hint
+    /// names a type parameter of `sig`. Bounds, also those of the `where` clause, are left to
+    /// the compiler
+    pub fn check_generics(&self, sig: &syn::Signature) -> syn::Result<()> {
+        let mut params = Vec::new();
+        for param in &sig.generics.params {
+            match param {
+                GenericParam::Type(param) => {
+                    if !self.generic.iter().any(|(name, _)| param.ident == name) {
+                        return Err(syn::Error::new_spanned(
+                            param,
+                            format!(
+                                "generic parameter `{}` of `{}` needs a concrete type ie #[generate_interface(generic({} = \"String\"))]",
+                                param.ident, sig.ident, param.ident
+                            ),
+                        ));
+                    }
+                    params.push(&param.ident);
+                }
+                GenericParam::Lifetime(_) => {}
+                GenericParam::Const(param) => {
+                    return Err(syn::Error::new_spanned(
+                        param,
+                        format!(
+                            "const generic parameters of `{}` aren't supported",
+                            sig.ident
+                        ),
+                    ))
+                }
+            }
+        }
+        match self
+            .generic
+            .iter()
+            .find(|(name, _)| !params.iter().any(|it| *it == name))
+        {
+            Some((name, _)) => Err(syn::Error::new_spanned(
+                &sig.ident,
+                format!("`{}` isn't a generic parameter of `{}`", name, sig.ident),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the `generic(...)` hints and the `skip_arg`s against `sig`
+    pub fn check_signature(&self, sig: &syn::Signature) -> syn::Result<()> {
+        self.check_generics(sig)?;
+        self.check_skip_args(sig)
+    }
+
+    /// Checks every `skip_arg` names a parameter of `sig`
+    pub fn check_skip_args(&self, sig: &syn::Signature) -> syn::Result<()> {
+        let is_param = |name: &String| {
+            sig.inputs.iter().any(|input| match input {
+                syn::FnArg::Typed(val) => {
+                    matches!(&*val.pat, syn::Pat::Ident(pat) if pat.ident == name)
+                }
+                syn::FnArg::Receiver(_) => false,
+            })
+        };
+        match self.skip_args.iter().find(|it| !is_param(it)) {
+            Some(name) => Err(syn::Error::new_spanned(
+                &sig.ident,
+                format!("`{}` isn't a parameter of `{}`", name, sig.ident),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses the arguments of an already attached `#[generate_interface(...)]`
+    pub fn from_attribute(attr: &syn::Attribute) -> syn::Result<InterfaceArgs> {
+        if attr.tokens.is_empty() {
+            return Ok(InterfaceArgs::default());
+        }
+        match attr.parse_meta()? {
+            Meta::List(list) => InterfaceArgs::parse(&list.nested),
+            meta => Err(syn::Error::new_spanned(
+                meta,
+                "expected generate_interface(...)",
+            )),
+        }
+    }
+}
+
+/// The arguments of `#[generate_interface_doc(...)]`
+#[derive(Debug, Default)]
+pub struct DocArgs {
+    /// `#[generate_interface_doc(instantiate(T = "String"))]`. The type each type parameter of a
+    /// generic struct is replaced with
+    pub instantiate: Vec<(String, syn::Type)>,
+}
+
+impl DocArgs {
+    /// Parses the comma separated arguments of the attribute
+    pub fn parse<'a>(args: impl IntoIterator<Item = &'a NestedMeta>) -> syn::Result<DocArgs> {
+        let mut result = DocArgs::default();
+        for arg in args {
+            match arg {
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("instantiate") => {
+                    result.instantiate.extend(parse_type_hints(list)?)
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        "unsupported generate_interface_doc argument",
+                    ))
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Checks every type parameter of the struct `ident` is instantiated and every
+    /// instantiated parameter is one of them
+    pub fn check_instantiations(
+        &self,
+        ident: &syn::Ident,
+        generics: &syn::Generics,
+    ) -> syn::Result<()> {
+        let mut params = Vec::new();
+        for param in &generics.params {
+            match param {
+                GenericParam::Type(param) => {
+                    if !self.instantiate.iter().any(|(name, _)| param.ident == name) {
+                        return Err(syn::Error::new_spanned(
+                            param,
+                            format!(
+                                "type parameter `{}` of `{}` needs a concrete type ie #[generate_interface_doc(instantiate({} = \"String\"))]",
+                                param.ident, ident, param.ident
+                            ),
+                        ));
+                    }
+                    params.push(&param.ident);
+                }
+                GenericParam::Lifetime(_) => {}
+                GenericParam::Const(param) => {
+                    return Err(syn::Error::new_spanned(
+                        param,
+                        format!("const generic parameters of `{}` aren't supported", ident),
+                    ))
+                }
+            }
+        }
+        match self
+            .instantiate
+            .iter()
+            .find(|(name, _)| !params.iter().any(|it| *it == name))
+        {
+            Some((name, _)) => Err(syn::Error::new_spanned(
+                ident,
+                format!("`{}` isn't a type parameter of `{}`", name, ident),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses the arguments of an already attached `#[generate_interface_doc(...)]`
+    pub fn from_attribute(attr: &syn::Attribute) -> syn::Result<DocArgs> {
+        if attr.tokens.is_empty() {
+            return Ok(DocArgs::default());
+        }
+        match attr.parse_meta()? {
+            Meta::List(list) => DocArgs::parse(&list.nested),
+            meta => Err(syn::Error::new_spanned(
+                meta,
+                "expected generate_interface_doc(...)",
+            )),
+        }
+    }
+}
+
+/// Parses `name(T = "Type", ...)` ie the hints of `generic(...)` and `instantiate(...)`
+fn parse_type_hints(list: &MetaList) -> syn::Result<Vec<(String, syn::Type)>> {
+    let name = list
+        .path
+        .get_ident()
+        .map(|it| it.to_string())
+        .unwrap_or_default();
+    let mut hints = Vec::with_capacity(list.nested.len());
+    for hint in &list.nested {
+        match hint {
+            NestedMeta::Meta(Meta::NameValue(val)) if val.path.get_ident().is_some() => {
+                let ty = match &val.lit {
+                    Lit::Str(ty) => ty.parse::<syn::Type>()?,
+                    lit => return Err(syn::Error::new_spanned(lit, "expected a type in quotes")),
+                };
+                hints.push((val.path.get_ident().unwrap().to_string(), ty));
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    hint,
+                    format!("expected {}(T = \"Type\")", name),
+                ))
+            }
+        }
+    }
+    Ok(hints)
+}
+
+/// Types of the fields a value struct has accessors for
+const VALUE_FIELD_TYPES: [&str; 15] = [
+    "bool", "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize", "f32", "f64",
+    "char", "String",
+];
+
+/// Whether `ty` is a zero sized marker ie `PhantomData<T>`, `PhantomPinned` or `()`. Marker
+/// fields get no accessors and constructors set them to their default
+fn is_marker(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Tuple(val) => val.elems.is_empty(),
+        syn::Type::Path(val) => val
+            .path
+            .segments
+            .last()
+            .is_some_and(|it| it.ident == "PhantomData" || it.ident == "PhantomPinned"),
+        _ => false,
+    }
+}
+
+/// The field of a `#[generate_interface(transparent)]` struct, which must be a tuple struct
+/// with a single field and no type parameters
+pub fn transparent_field(item: &syn::ItemStruct) -> syn::Result<&syn::Type> {
+    match &item.fields {
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 && item.generics.params.is_empty() => {
+            Ok(&fields.unnamed[0].ty)
+        }
+        _ => Err(syn::Error::new_spanned(
+            &item.ident,
+            format!(
+                "transparent structs need exactly one unnamed field and no generics ie `struct {}(f64);`",
+                item.ident
+            ),
+        )),
+    }
+}
+
+/// Generates the accessors of a `#[generate_interface(value)]` struct ie `get_x(&self) -> f64`
+/// and `set_x(&mut self, x: f64)` for each `pub` field of a primitive type or `String`, and a
+/// constructor when those are all of its fields apart from markers. Also returns the `pub`
+/// fields left out since their type isn't supported
+pub fn generate_value_accessors(item: &syn::ItemStruct) -> (ItemImpl, Vec<syn::Field>) {
+    let name = &item.ident;
+    let (markers, fields): (Vec<_>, Vec<_>) = item.fields.iter().partition(|it| is_marker(&it.ty));
+    let mut skipped = Vec::new();
+    let mut accessors = Vec::new();
+    let mut supported = 0;
+    for field in &fields {
+        let ident = match (&field.ident, &field.vis) {
+            (Some(ident), syn::Visibility::Public(_)) => ident,
+            _ => continue,
+        };
+        let ty = &field.ty;
+        let ty_name = match ty {
+            syn::Type::Path(val) if val.qself.is_none() => {
+                val.path.get_ident().map(|it| it.to_string())
+            }
+            _ => None,
+        };
+        let value = match ty_name.as_deref() {
+            Some("String") => quote::quote!(self.#ident.clone()),
+            Some(ty) if VALUE_FIELD_TYPES.contains(&ty) => quote::quote!(self.#ident),
+            _ => {
+                skipped.push((*field).clone());
+                continue;
+            }
+        };
+        supported += 1;
+        let getter = format_ident!("get_{}", ident);
+        let setter = format_ident!("set_{}", ident);
+        accessors.push(quote::quote! {
+            #[generate_interface]
+            pub fn #getter(&self) -> #ty {
+                #value
+            }
+
+            #[generate_interface]
+            pub fn #setter(&mut self, #ident: #ty) {
+                self.#ident = #ident;
+            }
+        });
+    }
+    let constructor = if supported > 0 && supported == fields.len() {
+        let idents = fields.iter().map(|it| &it.ident).collect::<Vec<_>>();
+        let types = fields.iter().map(|it| &it.ty);
+        let markers = markers.iter().map(|it| &it.ident);
+        quote::quote! {
+            #[generate_interface(constructor)]
+            pub fn new(#(#idents: #types),*) -> #name {
+                #name {
+                    #(#idents,)*
+                    #(#markers: ::core::default::Default::default(),)*
+                }
+            }
+        }
+    } else {
+        quote::quote!()
+    };
+    let impl_block = quote::quote! {
+        impl #name {
+            #constructor
+            #(#accessors)*
+        }
+    };
+    (syn::parse2(impl_block).unwrap(), skipped)
+}
 
 pub fn generate_impl_block(item: &syn::ItemStruct) -> ItemImpl {
     let name = item.clone().ident;
     let vis = item.clone().vis;
-    let fields = match item.clone().fields {
-        syn::Fields::Named(fields) => fields.named.into_iter().collect::<Vec<_>>(),
+    let (markers, fields): (Vec<_>, Vec<_>) = match item.clone().fields {
+        syn::Fields::Named(fields) => fields.named.into_iter().partition(|it| is_marker(&it.ty)),
         _ => unreachable!(),
     };
+    let markers = markers.iter().map(|it| &it.ident);
 
     let (f_setter, f_getter, f_ident, f_vis, f_ty): (Vec<_>, Vec<_>, Vec<_>, Vec<_>, Vec<_>) =
         fields
@@ -35,7 +483,8 @@ pub fn generate_impl_block(item: &syn::ItemStruct) -> ItemImpl {
                 #(#f_ident: #f_ty),*
             ) -> #name {
                 #name {
-                    #(#f_ident),*
+                    #(#f_ident,)*
+                    #(#markers: ::core::default::Default::default(),)*
                 }
             }
             #(
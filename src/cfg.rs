@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+use syn::{Attribute, Lit, Meta, NestedMeta};
+
+///The cfg options considered active when deciding whether an item is generated.
+/// When none are set every item is generated whatever its `#[cfg(...)]` says
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CfgSet {
+    options: HashSet<(String, String)>,
+}
+
+impl CfgSet {
+    ///`value` may be empty for options without values ie `unix`
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.options.insert((key.to_string(), value.to_string()));
+    }
+
+    ///`key = "value"` for every option, sorted
+    pub fn entries(&self) -> impl Iterator<Item = String> {
+        let mut entries = self
+            .options
+            .iter()
+            .map(|(key, value)| format!("{} = {:?}", key, value))
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries.into_iter()
+    }
+
+    ///Whether all the `#[cfg(...)]` attributes in `attrs` hold
+    pub fn is_active(&self, attrs: &[Attribute]) -> bool {
+        if self.options.is_empty() {
+            return true;
+        }
+        attrs
+            .iter()
+            .filter(|it| it.path.is_ident("cfg"))
+            .all(|it| match it.parse_meta() {
+                Ok(Meta::List(list)) => list.nested.iter().all(|it| self.evaluate(it)),
+                //leave what can't be understood to the compiler
+                _ => true,
+            })
+    }
+
+    fn evaluate(&self, predicate: &NestedMeta) -> bool {
+        match predicate {
+            NestedMeta::Meta(Meta::Path(path)) => path
+                .get_ident()
+                .is_none_or(|name| self.options.iter().any(|(key, _)| name == key)),
+            NestedMeta::Meta(Meta::NameValue(val)) => match (val.path.get_ident(), &val.lit) {
+                (Some(key), Lit::Str(value)) => {
+                    self.options.contains(&(key.to_string(), value.value()))
+                }
+                _ => true,
+            },
+            NestedMeta::Meta(Meta::List(list)) => {
+                let mut predicates = list.nested.iter();
+                if list.path.is_ident("all") {
+                    predicates.all(|it| self.evaluate(it))
+                } else if list.path.is_ident("any") {
+                    predicates.any(|it| self.evaluate(it))
+                } else if list.path.is_ident("not") {
+                    !predicates.all(|it| self.evaluate(it))
+                } else {
+                    true
+                }
+            }
+            NestedMeta::Lit(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::CfgSet;
+
+    fn is_active(cfg: &CfgSet, attr: &str) -> bool {
+        let item: syn::ItemStruct = syn::parse_str(&format!("{} struct Foo;", attr)).unwrap();
+        cfg.is_active(&item.attrs)
+    }
+
+    #[test]
+    fn predicates() {
+        let mut cfg = CfgSet::default();
+        assert!(is_active(&cfg, "#[cfg(target_os = \"ios\")]"));
+        cfg.insert("target_os", "android");
+        cfg.insert("unix", "");
+        assert!(is_active(&cfg, "#[cfg(target_os = \"android\")]"));
+        assert!(!is_active(&cfg, "#[cfg(not(target_os = \"android\"))]"));
+        assert!(is_active(
+            &cfg,
+            "#[cfg(all(unix, target_os = \"android\"))]"
+        ));
+        assert!(!is_active(
+            &cfg,
+            "#[cfg(all(windows, target_os = \"android\"))]"
+        ));
+        assert!(is_active(
+            &cfg,
+            "#[cfg(any(windows, target_os = \"android\"))]"
+        ));
+        assert!(is_active(&cfg, "#[derive(Debug)]"));
+    }
+}
@@ -1,7 +1,9 @@
-use crate::types_structs::{Enum, Struct, Trait};
+use crate::types_structs::{Enum, ItemInfo, Struct, Trait};
+use crate::{EnumCase, Indent, TypeCases};
+use std::path::PathBuf;
 
 ///Supported types
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Types {
     Struct,
     Trait,
@@ -16,36 +18,112 @@ pub enum TypeHolder {
 }
 
 impl TypeHolder {
-    pub fn generate_interface(&mut self) -> String {
+    pub fn generate_interface(
+        &mut self,
+        type_case: TypeCases,
+        enum_case: EnumCase,
+        indent: Indent,
+    ) -> String {
         match self {
-            TypeHolder::Trait(ref mut val) => val.generate_interface(),
-            TypeHolder::Struct(ref mut val) => val.generate_interface(),
-            TypeHolder::Enum(ref mut val) => val.generate_interface(),
+            TypeHolder::Trait(ref mut val) => val.generate_interface(type_case, enum_case, indent),
+            TypeHolder::Struct(ref mut val) => val.generate_interface(type_case, enum_case, indent),
+            TypeHolder::Enum(ref mut val) => val.generate_interface(type_case, enum_case, indent),
         }
     }
 
-    pub fn types(&self) -> Vec<&String> {
+    pub fn types(&self) -> Vec<String> {
         let mut types = Vec::new();
         match self {
             TypeHolder::Struct(val) => {
                 val.extras
                     .iter()
                     .filter_map(|it| it.method_info.as_ref())
-                    .for_each(|it| types.append(&mut it.all_types().collect::<Vec<&String>>()));
+                    .for_each(|it| types.append(&mut it.all_types()));
             }
             TypeHolder::Trait(val) => {
                 val.extras
                     .iter()
                     .filter_map(|it| it.method_info.as_ref())
-                    .for_each(|it| types.append(&mut it.all_types().collect::<Vec<&String>>()));
-            }
-            _ => {
-                unimplemented!()
+                    .for_each(|it| types.append(&mut it.all_types()));
             }
+            //unit variants use no types
+            TypeHolder::Enum(_) => {}
         }
         types
     }
 
+    ///The doc comment of this type
+    pub fn docs_mut(&mut self) -> &mut Vec<String> {
+        match self {
+            TypeHolder::Struct(val) => &mut val.docs,
+            TypeHolder::Trait(val) => &mut val.docs,
+            TypeHolder::Enum(val) => &mut val.docs,
+        }
+    }
+
+    ///The note of `#[deprecated]` on this type
+    pub fn deprecated(&self) -> Option<&str> {
+        match self {
+            TypeHolder::Struct(val) => val.deprecated.as_deref(),
+            TypeHolder::Trait(val) => val.deprecated.as_deref(),
+            TypeHolder::Enum(val) => val.deprecated.as_deref(),
+        }
+    }
+
+    ///The methods or variants of this type
+    pub fn extras_mut(&mut self) -> &mut Vec<ItemInfo> {
+        match self {
+            TypeHolder::Struct(val) => &mut val.extras,
+            TypeHolder::Trait(val) => &mut val.extras,
+            TypeHolder::Enum(val) => &mut val.extras,
+        }
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        match self {
+            TypeHolder::Struct(val) => val.name = name,
+            TypeHolder::Trait(val) => val.name = name,
+            TypeHolder::Enum(val) => val.name = name,
+        }
+    }
+
+    ///Set for types known by their path, see [`crate::paths::KnownTypes`]
+    pub fn set_foreign_name(&mut self, foreign_name: Option<String>) {
+        match self {
+            TypeHolder::Struct(val) => val.foreign_name = foreign_name,
+            TypeHolder::Trait(val) => val.foreign_name = foreign_name,
+            TypeHolder::Enum(val) => val.foreign_name = foreign_name,
+        }
+    }
+
+    ///Records the source file the type was parsed from
+    pub fn set_file(&mut self, file: PathBuf) {
+        match self {
+            TypeHolder::Struct(val) => val.file = Some(file),
+            TypeHolder::Trait(val) => val.file = Some(file),
+            TypeHolder::Enum(val) => val.file = Some(file),
+        }
+    }
+
+    ///Set for types whose rust type isn't their name, see [`crate::types_structs::Struct::self_type`]
+    pub fn set_self_type(&mut self, self_type: Option<String>) {
+        match self {
+            TypeHolder::Struct(val) => val.self_type = self_type,
+            TypeHolder::Trait(val) => val.self_type = self_type,
+            TypeHolder::Enum(val) => val.self_type = self_type,
+        }
+    }
+
+    ///The name of the class, callback or enum in the interface file
+    pub fn foreign_name(&self) -> &str {
+        let (name, foreign_name) = match self {
+            TypeHolder::Struct(val) => (&val.name, &val.foreign_name),
+            TypeHolder::Trait(val) => (&val.name, &val.foreign_name),
+            TypeHolder::Enum(val) => (&val.name, &val.foreign_name),
+        };
+        foreign_name.as_ref().unwrap_or(name)
+    }
+
     pub fn name(&self) -> &str {
         match self {
             TypeHolder::Struct(val) => val.name.as_str(),
@@ -55,6 +133,47 @@ impl TypeHolder {
     }
 }
 
+///flapigen only supports C-like enums so variants can't carry data
+pub fn check_unit_variant(variant: &syn::Variant) -> Result<(), String> {
+    let kind = match variant.fields {
+        syn::Fields::Unit => return Ok(()),
+        syn::Fields::Named(_) => "struct",
+        syn::Fields::Unnamed(_) => "tuple",
+    };
+    Err(format!(
+        "{} variants aren't supported since flapigen only supports C-like enums. \
+        Use an enum without data or wrap the data in a struct with an enum field instead",
+        kind
+    ))
+}
+
+///The value of the explicit discriminant of `variant` ie `10` for `Warn = 10`.
+/// Only integer literals, which may be negative, are supported
+pub fn discriminant(variant: &syn::Variant) -> Result<Option<i128>, String> {
+    let expr = match &variant.discriminant {
+        Some((_, expr)) => expr,
+        None => return Ok(None),
+    };
+    let (negative, lit) = match expr {
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => (true, &**expr),
+        expr => (false, expr),
+    };
+    match lit {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(val),
+            ..
+        }) => val
+            .base10_parse::<i128>()
+            .map(|it| Some(if negative { -it } else { it }))
+            .map_err(|err| err.to_string()),
+        _ => Err("only integer literals are supported as discriminants".into()),
+    }
+}
+
 ///`Current` refers to just adding a new line\
 /// `ShiftRight` refers to adding a new line and then a tab more than the previous line\
 /// `ShiftLeft` refers to adding a new line and then a tab less than the previous line
@@ -0,0 +1,174 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Errors that can occur while generating the interface file
+#[derive(Debug)]
+pub enum GenError {
+    /// Reading the source files or writing the interface file failed
+    Io(std::io::Error),
+    /// A source file couldn't be parsed as rust code
+    Parse {
+        file: PathBuf,
+        location: Location,
+        source: syn::Error,
+    },
+    /// An annotated item can't be expressed in the interface file
+    Unsupported {
+        file: PathBuf,
+        /// Where the item is in `file` when it's known
+        location: Option<Location>,
+        /// Name of the offending item
+        item: String,
+        /// Why the item isn't supported
+        reason: String,
+    },
+    /// [`crate::Generator::check`] found the interface file out of date
+    Stale(Diff),
+    /// The markers of an interface file written with [`crate::Generator::append`] are out of
+    /// order or one is missing, so it's left untouched
+    Markers { file: PathBuf, reason: String },
+    /// A value given to a builder method of [`crate::Generator`] is invalid
+    Setting {
+        /// The builder method ie `java_package`
+        setting: String,
+        reason: String,
+    },
+}
+
+/// The first difference between an interface file and what would be generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diff {
+    pub file: PathBuf,
+    /// Starts at 1
+    pub line: usize,
+    /// The line in the file, `None` past its end
+    pub existing: Option<String>,
+    /// The generated line, `None` past the end of the generated interface
+    pub generated: Option<String>,
+}
+
+impl Diff {
+    /// Compares the lines of `existing` and `generated`, ignoring trailing whitespace
+    pub(crate) fn between(file: PathBuf, existing: &str, generated: &str) -> Option<Diff> {
+        Diff::all_between(file, existing, generated)
+            .into_iter()
+            .next()
+    }
+
+    /// Every line that differs, comparing the lines with the same number
+    pub(crate) fn all_between(file: PathBuf, existing: &str, generated: &str) -> Vec<Diff> {
+        fn lines(text: &str) -> Vec<&str> {
+            let mut lines = text.lines().map(str::trim_end).collect::<Vec<_>>();
+            while lines.last() == Some(&"") {
+                lines.pop();
+            }
+            lines
+        }
+        let (existing, generated) = (lines(existing), lines(generated));
+        (0..existing.len().max(generated.len()))
+            .filter(|&i| existing.get(i) != generated.get(i))
+            .map(|line| Diff {
+                file: file.to_path_buf(),
+                line: line + 1,
+                existing: existing.get(line).map(|it| it.to_string()),
+                generated: generated.get(line).map(|it| it.to_string()),
+            })
+            .collect()
+    }
+}
+
+/// Position of an item in a source file. Both the line and column start at 1
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// Must be called on the thread which parsed the file `span` is from
+    pub(crate) fn of(span: proc_macro2::Span) -> Location {
+        let start = span.start();
+        Location {
+            line: start.line,
+            column: start.column + 1,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl Display for GenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenError::Io(err) => write!(f, "{}", err),
+            GenError::Parse {
+                file,
+                location,
+                source,
+            } => {
+                write!(
+                    f,
+                    "Unable to parse {}:{}: {}",
+                    file.display(),
+                    location,
+                    source
+                )
+            }
+            GenError::Unsupported {
+                file,
+                location,
+                item,
+                reason,
+            } => {
+                write!(f, "Unsupported item `{}` in {}", item, file.display())?;
+                if let Some(location) = location {
+                    write!(f, ":{}", location)?;
+                }
+                write!(f, ": {}", reason)
+            }
+            GenError::Setting { setting, reason } => write!(f, "Invalid {}: {}", setting, reason),
+            GenError::Markers { file, reason } => {
+                write!(f, "Unable to merge into {}: {}", file.display(), reason)
+            }
+            GenError::Stale(diff) => {
+                let line = |line: &Option<String>| match line {
+                    Some(line) => format!("`{}`", line),
+                    None => "end of file".to_string(),
+                };
+                write!(
+                    f,
+                    "{} is out of date, line {} is {} instead of {}",
+                    diff.file.display(),
+                    diff.line,
+                    line(&diff.existing),
+                    line(&diff.generated)
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GenError::Io(err) => Some(err),
+            GenError::Parse { source, .. } => Some(source),
+            GenError::Unsupported { .. }
+            | GenError::Stale(_)
+            | GenError::Markers { .. }
+            | GenError::Setting { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for GenError {
+    fn from(err: std::io::Error) -> Self {
+        GenError::Io(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, GenError>;
@@ -1,64 +1,177 @@
-use crate::enums::{TypeHolder, Types};
-use crate::types_structs::{Enum, ItemInfo, Struct, Trait, TYPE_CASE};
-use crate::{Language, TypeCases};
+use crate::enums::{check_unit_variant, discriminant, TypeHolder, Types};
+use crate::error::{GenError, Location, Result};
+use crate::maps::{is_primitive, RustType, TypeMap};
+use crate::model::{GenSummary, Interface};
+use crate::paths::{file_module, foreign_name, instantiated_name, qualify, KnownTypes};
+use crate::symbols;
+use crate::text_formatter::{convert_doc, deprecation_doc, with_line_ending};
+use crate::traits::check_callback_output;
+use crate::types_structs::{Enum, ItemInfo, MethodInfo, Struct, Trait};
+use crate::{ConstructorPolicy, Language, Order, Settings, TypeCases};
 use derive_new::new;
-use gen_attributes_utils::generate_impl_block;
-use std::collections::{HashMap, VecDeque};
-use std::fs::{DirEntry, File};
-use std::io::Write;
-use std::path::Path;
+use gen_attributes_utils::{
+    generate_impl_block, generate_value_accessors, transparent_field, DocArgs, InterfaceArgs,
+};
+use log::{debug, info, warn};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::time::Instant;
+use std::sync::Mutex;
+use std::time::{Instant, UNIX_EPOCH};
 use syn::__private::ToTokens;
-use syn::{ItemStruct, PathArguments, ReturnType, Type};
+use syn::spanned::Spanned;
+use syn::{Attribute, ItemStruct, ReturnType};
 
 //constants
 
 pub const F_CLASS: &str = "foreign_class!";
 pub const F_CALLBACK: &str = "foreign_callback!";
 pub const F_ENUM: &str = "foreign_enum!";
-
-//errors
-const UNABLE_TO_READ: &str = "Unable to read file: ";
+const SPLIT_HEADER: &str = "header.in";
+const SPLIT_INDEX: &str = "index.txt";
 
 //helper macros
 #[derive(new, Debug)]
 struct AttrCheck {
     is_attribute: bool,
-    is_constructor: bool,
+    args: InterfaceArgs,
 }
 
 fn has_gen_access_methods_attr(item: &ItemStruct) -> bool {
     item.attrs
         .iter()
-        .map(|attr| attr.path.segments.iter())
-        .flatten()
+        .flat_map(|attr| attr.path.segments.iter())
         .any(|seg| seg.ident == "generate_access_methods")
 }
 
-macro_rules! has_gen_attr {
-    ($expr:expr) => {
-        has_gen_attr!($expr, false)
+///The arguments of `#[generate_interface_doc(...)]`. The attribute macro already rejects invalid
+/// arguments at compile time
+fn doc_gen_args(attrs: &[Attribute]) -> DocArgs {
+    attrs
+        .iter()
+        .find(|it| {
+            it.path
+                .segments
+                .iter()
+                .any(|it| it.ident == "generate_interface_doc")
+        })
+        .and_then(|it| DocArgs::from_attribute(it).ok())
+        .unwrap_or_default()
+}
+
+///Adds the methods of the annotated supertraits of each callback, and of theirs, to the
+/// callback since the foreign implementation must implement them too. They keep the path of the
+/// trait they're declared in
+fn inherit_methods(
+    file_data: &mut HashMap<Rc<String>, TypeHolder>,
+    supertraits: &HashMap<String, Vec<String>>,
+) {
+    for (name, parents) in supertraits {
+        let mut inherited = Vec::new();
+        let mut seen = HashSet::from([name.to_string()]);
+        let mut queue = parents.iter().cloned().collect::<VecDeque<_>>();
+        while let Some(parent) = queue.pop_front() {
+            if !seen.insert(parent.to_string()) {
+                continue;
+            }
+            if let Some(TypeHolder::Trait(val)) = file_data.get(&parent) {
+                for extra in &val.extras {
+                    let mut extra = extra.clone();
+                    extra
+                        .inherited_from
+                        .get_or_insert_with(|| parent.to_string());
+                    inherited.push(extra);
+                }
+            }
+            queue.extend(supertraits.get(&parent).into_iter().flatten().cloned());
+        }
+        if let Some(TypeHolder::Trait(val)) = file_data.get_mut(name) {
+            for extra in inherited {
+                if !val.extras.iter().any(|it| it.signature == extra.signature) {
+                    val.extras.push(extra);
+                }
+            }
+        }
+    }
+}
+
+///Generic parameters are replaced with the types given in `generic(...)`
+fn generic_types(args: &InterfaceArgs) -> TypeMap {
+    let mut generics = TypeMap::default();
+    for (param, ty) in &args.generic {
+        generics.insert(
+            RustType::Path {
+                segments: vec![param.to_string()],
+                args: vec![],
+            },
+            RustType::from_syn(ty),
+        );
+    }
+    generics
+}
+
+///Whether `sig` has a `where Self: Sized` bound, which leaves the method out of `dyn Trait`
+fn requires_sized(sig: &syn::Signature) -> bool {
+    sig.generics
+        .where_clause
+        .iter()
+        .flat_map(|it| &it.predicates)
+        .any(|it| match it {
+            syn::WherePredicate::Type(val) => {
+                matches!(&val.bounded_ty, syn::Type::Path(ty) if ty.path.is_ident("Self"))
+                    && val.bounds.iter().any(|it| {
+                        matches!(it, syn::TypeParamBound::Trait(bound) if bound.path.segments.last().is_some_and(|it| it.ident == "Sized"))
+                    })
+            }
+            _ => false,
+        })
+}
+
+///The note of `#[deprecated]`, `#[deprecated = "note"]` or `#[deprecated(note = "note")]`,
+/// empty when there's none and `None` when the item isn't deprecated
+fn deprecation(attrs: &[Attribute]) -> Option<String> {
+    let attr = attrs.iter().find(|it| it.path.is_ident("deprecated"))?;
+    let note = match attr.parse_meta() {
+        Ok(syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(val),
+            ..
+        })) => Some(val.value()),
+        Ok(syn::Meta::List(list)) => list.nested.iter().find_map(|it| match it {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(val)) if val.path.is_ident("note") => {
+                match &val.lit {
+                    syn::Lit::Str(val) => Some(val.value()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }),
+        _ => None,
     };
-    ($expr:expr,$check_for_constructor:expr) => {{
+    Some(note.unwrap_or_default())
+}
+
+macro_rules! has_gen_attr {
+    ($expr:expr) => {{
         let mut is_attribute = false;
-        let mut is_constructor = false;
+        let mut args = InterfaceArgs::default();
         $expr.attrs.iter().any(|it| {
             is_attribute = it
                 .path
                 .segments
                 .iter()
                 .any(|it| it.ident == "generate_interface");
-            if is_attribute && $check_for_constructor {
-                is_constructor = it.tokens.to_string().contains("constructor");
+            if is_attribute {
+                //the attribute macro already rejects invalid arguments at compile time
+                args = InterfaceArgs::from_attribute(it).unwrap_or_default();
             }
             is_attribute
         });
-        AttrCheck::new(is_attribute, is_constructor)
+        AttrCheck::new(is_attribute, args)
     }};
 }
 
-//to use with Structs only
 macro_rules! has_doc_gen_attr {
     ($expr:expr) => {
         $expr.attrs.iter().any(|it| {
@@ -74,111 +187,59 @@ macro_rules! get_doc {
         $expr
             .attrs
             .iter()
-            .filter_map(|it| {
-                //segments usually contain an item but just in case
-                let doc = it
-                    .path
-                    .segments
-                    .iter()
-                    .map(|it| it.ident.to_string())
-                    .filter(|it| it == "doc")
-                    .next();
-                //
-                if let Some(_) = doc {
-                    Some(it.to_token_stream().to_string())
-                } else {
-                    None
-                }
+            .filter(|it| it.path.is_ident("doc"))
+            //the text of `///text` which is `#[doc = "text"]`
+            .filter_map(|it| match it.parse_meta() {
+                Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                    lit: syn::Lit::Str(val),
+                    ..
+                })) => Some(val.value()),
+                _ => None,
             })
+            //block comments may have several lines
+            .flat_map(|it| it.lines().map(str::to_string).collect::<Vec<_>>())
             .collect::<Vec<String>>()
     };
 }
 
-macro_rules! correct_string {
-    ($string:expr) => {{
-        let sign = $string.rfind("<");
-        if let Some(sign) = sign {
-            //means it's something like Box<Kofi>
-            // find the other >
-            let other = $string.find(">").unwrap();
-            let new_string = &$string[sign + 1..other];
-            new_string.trim().to_string()
-        } else {
-            $string.trim().into()
-        }
-    }};
-}
-
-macro_rules! types_in_method {
-    ($expr:expr) => {{
-        let result = ($expr)
-            .sig
-            .inputs
-            .iter()
-            .filter_map(|it| {
-                match it {
-                    syn::FnArg::Receiver(_) => None, //something like (&self)
-                    syn::FnArg::Typed(typ) => {
-                        let _type = typ.ty.to_token_stream().to_string().replace(" dyn ", "");
-                        ////println!("type {}", _type);
-                        Some(correct_string!(_type))
-                    }
-                }
-            })
-            .collect::<Vec<_>>();
-        result
-    }};
-}
-
-macro_rules! return_types {
+macro_rules! method_info {
     ($expr:expr) => {{
-        let mut return_types: Vec<String> = Vec::new();
-        match &$expr.sig.output {
-            ReturnType::Type(_, val) => match &**val {
-                Type::Path(val) => {
-                    //let result = vec![];
-                    val.path.segments.iter().for_each(|it| {
-                        return_types.push((&it.ident).to_string());
-                        match &it.arguments {
-                            PathArguments::AngleBracketed(val) => {
-                                val.args.iter().for_each(|it| {
-                                    let str = it.to_token_stream().to_string();
-                                    return_types.push(str);
-                                });
-                            }
-                            _ => {}
+        let signature = &$expr.sig;
+        let mut receiver = None;
+        let mut args = Vec::with_capacity(signature.inputs.len());
+        for input in &signature.inputs {
+            match input {
+                syn::FnArg::Receiver(val) => {
+                    //`mut self` is the same as `self` to the foreign side
+                    receiver = Some(
+                        match (&val.reference, &val.mutability) {
+                            (Some(_), Some(_)) => "&mut self",
+                            (Some(_), None) => "&self",
+                            (None, _) => "self",
                         }
-                    });
+                        .to_string(),
+                    )
                 }
-                _ => {}
-            },
-            _ => {}
-        }
-        return_types
-    }};
-}
-
-macro_rules! function_signature {
-    ($expr:expr) => {{
-        {
-            let signature = &$expr.sig;
-            let mut iter = signature.to_token_stream().into_iter();
-            //it could start with maybe unsafe fn or fn
-            iter.find(|it| it.to_string() == "fn");
-            let mut result = String::with_capacity(32);
-            while let Some(val) = iter.next() {
-                let val = val.to_string();
-                if val == "'" {
-                    //lifetimes cause problems
-                    result.push_str(&val); // push the lifetime param
-                    result.push_str(&iter.next().unwrap().to_string());
-                    result.push(' ');
-                } else {
-                    result.push_str(&val)
+                syn::FnArg::Typed(val) => {
+                    let name = match &*val.pat {
+                        syn::Pat::Ident(pat) => pat.ident.to_string(),
+                        pat => pat.to_token_stream().to_string(),
+                    };
+                    let ty = RustType::from_syn(&val.ty);
+                    if name == "self" {
+                        //`self: Arc<Self>` and the like are still instance methods
+                        receiver = Some(self_receiver(ty));
+                    } else {
+                        args.push((name, ty))
+                    }
                 }
             }
-            result
         }
+        let output = match &signature.output {
+            ReturnType::Default => None,
+            ReturnType::Type(_, ty) => Some(RustType::from_syn(ty)),
+        };
+        MethodInfo::new(signature.ident.to_string(), receiver, args, output)
     }};
 }
 
@@ -192,7 +253,8 @@ macro_rules! function_signature {
 /// so now it's time to add struct B
 /// struct B should be placed in front of struct A in vec deque
 struct ItemsHolder {
-    list: HashMap<Rc<String>, TypeHolder>,
+    //BTreeMaps so the order is the same on every run
+    list: BTreeMap<Rc<String>, TypeHolder>,
     enums_list: Vec<Enum>,
     final_list: VecDeque<Rc<String>>,
 }
@@ -200,7 +262,7 @@ struct ItemsHolder {
 impl ItemsHolder {
     fn new(capacity: usize) -> ItemsHolder {
         ItemsHolder {
-            list: HashMap::with_capacity(capacity),
+            list: BTreeMap::new(),
             enums_list: Vec::new(),
             final_list: VecDeque::with_capacity(capacity),
         }
@@ -228,70 +290,54 @@ impl ItemsHolder {
         //N has a method which depends on O
         // So in effect the list should be [O, N, F, ...] even though F was added first
         if self.list.is_empty() {
-            eprintln!("Annotate methods and enums to use module rust_interface_file_generator");
+            warn!("Annotate methods and enums to use module rust_interface_file_generator");
             return;
         }
-        self.list.keys().next().unwrap().to_string();
-        let mut values = self
-            .list
-            .keys()
-            .map(|it| (it.clone(), None))
-            .collect::<HashMap<Rc<String>, Option<()>>>();
-
-        //TODO optimise it
+        //each item is placed after the items it depends on. Otherwise items are in name order.
+        //Items that depend on each other are placed in the order they're reached
         fn analyse_item(
-            item: &TypeHolder,
-            values: &mut HashMap<Rc<String>, Option<()>>,
-            map: &HashMap<Rc<String>, TypeHolder>,
+            name: &Rc<String>,
+            visited: &mut HashSet<Rc<String>>,
+            map: &BTreeMap<Rc<String>, TypeHolder>,
             out: &mut VecDeque<Rc<String>>,
         ) {
-            let types = item.types();
-            //println!("types {:?} name {}", types, item.name());
-            let item_name = item.name().to_string();
-            values.remove(&item_name);
-            let item_name = Rc::new(item_name);
-            out.iter()
-                .position(|it| it == &item_name)
-                .map(|it| out.remove(it));
-            out.push_front(item_name);
-            for _type in types {
-                //println!("type {}", _type);
-                if let Some(val) = map.get(_type) {
-                    if val.name() == item.name() {
-                        //Since classes with constructors have `self` as type
-                        continue;
-                    }
-                    //println!("val {} item {}", val.name(), item.name());
-                    //println!("for ilist {:?}", out);
-                    analyse_item(val, values, map, out);
+            if !visited.insert(name.clone()) {
+                return;
+            }
+            let item = &map[name];
+            for _type in item.types() {
+                //Since classes with constructors have `self` as type, the item itself is
+                // already visited. Types are known by their name unless it's ambiguous
+                let name = _type.rsplit("::").next().unwrap_or_default().to_string();
+                if let Some((key, _)) = map
+                    .get_key_value(&_type)
+                    .or_else(|| map.get_key_value(&name))
+                {
+                    analyse_item(key, visited, map, out);
                 }
             }
-            //values.remove(&item.name().to_string());
-            ////println!("list {:?}", out);
-            //panic!();
-        }
-
-        //let mut times = 0;
-        while !values.is_empty() {
-            /*println!(
-                "while {:?} start {}",
-                values.keys().collect::<Vec<&Rc<String>>>(),
-                values.iter().next().unwrap().0.to_string()
-            );*/
-            analyse_item(
-                self.list.get(values.iter().next().unwrap().0).unwrap(),
-                &mut values,
-                &self.list,
-                &mut self.final_list,
-            );
-            //println!("panic {:?}", values.keys().collect::<Vec<&Rc<String>>>());
-            /*times += 1;
-            if times == 5 {
-                panic!()
-            }*/
-            //panic!()
-        }
-        ////println!("out {}",)
+            out.push_back(name.clone());
+        }
+
+        let mut visited = HashSet::with_capacity(self.list.len());
+        for name in self.list.keys() {
+            analyse_item(name, &mut visited, &self.list, &mut self.final_list);
+        }
+    }
+
+    fn from_interface(interface: Interface) -> ItemsHolder {
+        let mut holder = ItemsHolder::new(interface.classes.len() + interface.traits.len());
+        for val in interface.classes {
+            holder.add_items(Rc::new(val.name.to_string()), TypeHolder::Struct(val));
+        }
+        for val in interface.traits {
+            holder.add_items(Rc::new(val.name.to_string()), TypeHolder::Trait(val));
+        }
+        interface
+            .enums
+            .into_iter()
+            .for_each(|it| holder.add_enum(it));
+        holder
     }
 
     fn add_enum(&mut self, data: Enum) {
@@ -299,381 +345,1852 @@ impl ItemsHolder {
         self.enums_list.push(data)
     }
 
-    fn generate_interface<P: AsRef<Path>>(mut self, language: Language, out_file: P) {
-        //println!("final {:?}", self.final_list);
-        let mut file = File::create(out_file).expect("Unable to write to disk");
-        file.write_all(b"//Automatically generated by rifgen\nuse crate::*;\n")
-            .unwrap();
-        if matches!(language, Language::Java) {
-            file.write_all(b"use jni_sys::*;\n").unwrap();
+    ///The preamble shared by all the types and the name and interface of each type in the order
+    /// they should be written
+    fn generate_interface(
+        mut self,
+        language: Language,
+        type_case: TypeCases,
+        settings: &Settings,
+    ) -> (String, Vec<(String, String)>) {
+        let &Settings {
+            enum_case,
+            order,
+            convert_docs,
+            strip_doc_comments: strip_docs,
+            indent,
+            ..
+        } = settings;
+        let mut header = String::from("//Automatically generated by rifgen\nuse crate::*;\n");
+        if matches!(language, Language::Java | Language::Kotlin) {
+            header.push_str("use jni_sys::*;\n");
+        }
+        //flapigen calls methods of trait impls as `Foo::draw` so the traits must be in scope
+        let traits = self
+            .list
+            .values_mut()
+            .flat_map(|it| it.extras_mut().iter())
+            .filter_map(|it| it.implemented_trait.clone())
+            .collect::<BTreeSet<_>>();
+        for path in traits {
+            header.push_str(&format!("use {};\n", path));
+        }
+        //deprecated items are marked in their doc comments where the foreign tools look for it
+        let mark = |docs: &mut Vec<String>, deprecated: Option<&str>| {
+            if let Some(note) = deprecated {
+                docs.push(deprecation_doc(note, language));
+            }
+        };
+        for item in &mut self.enums_list {
+            mark(&mut item.docs, item.deprecated.as_deref());
+            for variant in &mut item.extras {
+                mark(&mut variant.docs, variant.deprecated.as_deref());
+            }
+        }
+        for item in self.list.values_mut() {
+            let deprecated = item.deprecated().map(str::to_string);
+            mark(item.docs_mut(), deprecated.as_deref());
+            for method in item.extras_mut() {
+                mark(&mut method.docs, method.deprecated.as_deref());
+            }
+        }
+        if strip_docs {
+            //discriminants are documented so they go too
+            for item in &mut self.enums_list {
+                item.docs.clear();
+                item.extras.iter_mut().for_each(|it| {
+                    it.docs.clear();
+                    it.discriminant = None;
+                });
+            }
+            for item in self.list.values_mut() {
+                item.docs_mut().clear();
+                item.extras_mut().iter_mut().for_each(|it| it.docs.clear());
+            }
+        } else if convert_docs {
+            let convert = |docs: &mut Vec<String>| {
+                docs.iter_mut()
+                    .for_each(|it| *it = convert_doc(it, language))
+            };
+            for item in &mut self.enums_list {
+                convert(&mut item.docs);
+                item.extras.iter_mut().for_each(|it| convert(&mut it.docs));
+            }
+            for item in self.list.values_mut() {
+                convert(item.docs_mut());
+                item.extras_mut()
+                    .iter_mut()
+                    .for_each(|it| convert(&mut it.docs));
+            }
         }
+        //constructors come first, then static methods and then the other methods, each sorted by name.
+        //In source order the methods after the constructors are sorted by where they're declared.
+        //Enum variants keep their order
+        for item in self.list.values_mut() {
+            item.extras_mut().sort_by(|a, b| {
+                let key = |it: &ItemInfo| {
+                    let group = match (&it.method_info, order) {
+                        _ if it.is_constructor => 0,
+                        (_, Order::SourceDeclaration) => 1,
+                        (Some(info), Order::Sorted) if info.is_static() => 1,
+                        _ => 2,
+                    };
+                    let position = match order {
+                        Order::SourceDeclaration => {
+                            (it.file.clone(), it.location.map(|it| (it.line, it.column)))
+                        }
+                        Order::Sorted => (None, None),
+                    };
+                    (group, position, it.signature.to_string())
+                };
+                key(a).cmp(&key(b))
+            });
+        }
+        let mut types = Vec::with_capacity(self.enums_list.len() + self.list.len());
         //first add enums since enums "can't" depend on other data structures
         self.sort_items();
+        self.enums_list.sort_by(|a, b| a.name.cmp(&b.name));
         for mut enums in self.enums_list {
-            file.write_all(enums.generate_interface().as_ref())
-                .expect("Unable to write to disk");
-        }
-
-        /*assert_eq!(
-            self.final_list,
-            vec![
-                Rc::new("OTH".into()),
-                Rc::new("Kofi".into()),
-                Rc::new("Noth".into()),
-                Rc::new("Finalise".into()),
-                Rc::new("ImDone".into())
-            ]
-        );*/
-        //println!("tested");
+            debug!("Emitting enum {}", enums.name);
+            let interface = enums.generate_interface(type_case, enum_case, indent);
+            types.push((enums.foreign_name.unwrap_or(enums.name), interface));
+        }
+
         for name in self.final_list {
-            file.write_all(
-                self.list
-                    .get_mut(&*name)
-                    .unwrap()
-                    .generate_interface()
-                    .as_ref(),
-            )
-            .expect("Unable to write to disk");
+            let item = self.list.get_mut(&*name).unwrap();
+            debug!("Emitting {}", name);
+            for extra in item.extras_mut() {
+                debug!("Emitting {}::{}", name, extra.signature);
+            }
+            let interface = item.generate_interface(type_case, enum_case, indent);
+            types.push((item.foreign_name().to_string(), interface));
         }
+        (header, types)
     }
 }
 // one possible implementation of walking a directory only visiting files
 fn visit_dirs<P: AsRef<Path>>(
     dir: P,
-    cb: &mut dyn FnMut(&std::fs::DirEntry),
-) -> std::io::Result<()> {
+    cb: &mut dyn FnMut(&std::fs::DirEntry) -> Result<()>,
+) -> Result<()> {
     if dir.as_ref().is_dir() {
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
+        //sorted so the output doesn't depend on the order the file system lists files
+        let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|it| it.path());
+        for entry in entries {
             let path = entry.path();
             if path.is_dir() {
                 visit_dirs(&path, cb)?;
             } else {
-                cb(&entry);
+                cb(&entry)?;
             }
         }
     }
     Ok(())
 }
 
-pub struct FileGenerator<I: AsRef<Path>, S: AsRef<Path>> {
-    interface_file_path: I,
-    starting_point: S,
+pub struct FileGenerator {
+    settings: Settings,
+    /// `TypeCases::Default` is `TypeCases::CamelCase` for Kotlin
+    type_case: TypeCases,
+    /// the C++ namespace or java package of the generated classes
+    package: Option<String>,
+    /// the built in mappings overridden by those of the settings
+    type_map: TypeMap,
+    /// the unsupported items skipped so far
+    skipped: Mutex<Vec<String>>,
 }
 
-impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
-    pub fn new(
-        type_case: TypeCases,
-        interface_file_path: I,
-        starting_point: S,
-    ) -> FileGenerator<I, S> {
-        unsafe { TYPE_CASE = type_case }
+impl FileGenerator {
+    ///The Kotlin case, the package and the type map are resolved for the language of `settings`
+    pub fn new(settings: Settings) -> FileGenerator {
+        let type_case = match (settings.language, settings.type_case) {
+            (Language::Kotlin, TypeCases::Default) => TypeCases::CamelCase,
+            (_, type_case) => type_case,
+        };
+        let package = match settings.language {
+            Language::Cpp => settings.cpp_namespace.clone(),
+            Language::Java | Language::Kotlin => settings.java_package.clone(),
+            Language::Python => None,
+        };
         FileGenerator {
-            interface_file_path,
-            starting_point,
+            type_case,
+            package,
+            type_map: settings.effective_type_map(),
+            settings,
+            skipped: Mutex::default(),
+        }
+    }
+
+    ///Every unsupported item goes through here. It's skipped with a warning, so the caller
+    /// moves on to the next item, unless unsupported items should fail the generation.
+    /// Other errors are always returned
+    fn skip_unsupported(&self, err: GenError) -> Result<()> {
+        match err {
+            GenError::Unsupported { .. } if !self.settings.fail_on_unsupported => {
+                warn!("Skipped {}", err);
+                self.skipped.lock().unwrap().push(err.to_string());
+                Ok(())
+            }
+            err => Err(err),
+        }
+    }
+
+    ///Writes `contents` to `path`. The file isn't touched, so its modification time is kept,
+    /// when it already holds `contents` unless every file should be written
+    pub fn write(&self, path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+        let contents = contents.as_ref();
+        if self.settings.overwrite_only_if_changed
+            && std::fs::read(path).is_ok_and(|it| it == contents)
+        {
+            return Ok(());
         }
+        std::fs::write(path, contents)?;
+        Ok(())
     }
 
-    pub fn build(&self, language: Language) {
+    ///Writes the interface to `writer`. Writes are buffered so `writer` needn't be buffered.
+    /// Files are parsed in parallel but the output doesn't depend on the number of threads
+    pub fn build<W: Write>(&self, language: Language, writer: W) -> Result<()> {
         let start = Instant::now();
-        //the closure to be applied to each file
+        let (header, types) = self.generate(language)?;
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(header.as_bytes())?;
+        for (_, interface) in types {
+            writer.write_all(interface.as_bytes())?;
+        }
+        writer.flush()?;
+        info!("Total Time Taken To Generate File {:?}", start.elapsed());
+        Ok(())
+    }
+
+    ///Writes the interface of each type to its own file in `dir` along with `header.in`, which
+    /// has what's shared by all of them, and `index.txt`, which lists the files in the order they
+    /// should be concatenated.
+    /// Types are written to `<name>.in`. Since file names may not be case sensitive, types whose
+    /// names only differ by case after the first, in name order, get a `_2`, `_3`.. suffix.
+    /// When cleaning, the files the previous index lists that aren't written anymore are removed
+    pub fn build_split(&self, language: Language, dir: &Path) -> Result<()> {
+        let start = Instant::now();
+        let (header, types) = self.generate(language)?;
+        let file_names = split_file_names(types.iter().map(|(name, _)| name.as_str()));
+        //only read once generating succeeded so a failure leaves the old files in place
+        let previous = match self.settings.clean {
+            true => std::fs::read_to_string(dir.join(SPLIT_INDEX)).unwrap_or_default(),
+            false => String::new(),
+        };
+        std::fs::create_dir_all(dir)?;
+        self.write(&dir.join(SPLIT_HEADER), header)?;
+        let mut index = format!("{}\n", SPLIT_HEADER);
+        for ((_, interface), file_name) in types.iter().zip(&file_names) {
+            self.write(&dir.join(file_name), interface)?;
+            index.push_str(file_name);
+            index.push('\n');
+        }
+        self.write(
+            &dir.join(SPLIT_INDEX),
+            with_line_ending(index, self.settings.line_ending),
+        )?;
+        //the index could have been edited so only file names of fragments are trusted
+        let stale = previous.lines().map(str::trim).filter(|it| {
+            it.ends_with(".in")
+                && *it != SPLIT_HEADER
+                && Path::new(it).file_name().is_some_and(|name| name == *it)
+                && !file_names.iter().any(|name| name == it)
+        });
+        for file_name in stale {
+            match std::fs::remove_file(dir.join(file_name)) {
+                Ok(()) => info!("Removed {} from {}", file_name, dir.display()),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        info!("Total Time Taken To Generate Files {:?}", start.elapsed());
+        Ok(())
+    }
+
+    ///The preamble shared by all types and the name and interface of each type
+    fn generate(&self, language: Language) -> Result<(String, Vec<(String, String)>)> {
+        let interface = self.collect_for(language)?;
+        if let Some(path) = &self.settings.symbols {
+            let json = symbols::to_json(&interface, self.type_case, self.settings.enum_case);
+            self.write(path, with_line_ending(json, self.settings.line_ending))?;
+        }
+        let (preamble, types) = ItemsHolder::from_interface(interface).generate_interface(
+            language,
+            self.type_case,
+            &self.settings,
+        );
+        //the interface file is rust code whatever the language so the banner is a rust comment
+        let mut header = String::new();
+        if let Some(banner) = &self.settings.banner {
+            for line in banner.lines() {
+                header.push_str("//");
+                if !line.is_empty() {
+                    header.push(' ');
+                    header.push_str(line);
+                }
+                header.push('\n');
+            }
+        }
+        //flapigen takes them from its config so they're only recorded
+        match (&self.package, language) {
+            (Some(namespace), Language::Cpp) => header.push_str(&format!(
+                "//C++ namespace: {0}. Use CppConfig::new(output_dir, \"{0}\".into())\n",
+                namespace
+            )),
+            (Some(package), _) => header.push_str(&format!(
+                "//Java package: {0}. Use JavaConfig::new(output_dir, \"{0}\".into())\n",
+                package
+            )),
+            (None, _) => {}
+        }
+        header.push_str(&preamble);
+        let types = types
+            .into_iter()
+            .map(|(name, interface)| (name, with_line_ending(interface, self.settings.line_ending)))
+            .collect();
+        Ok((with_line_ending(header, self.settings.line_ending), types))
+    }
+
+    ///Counts what [`FileGenerator::collect_for`] finds and lists what it skips
+    pub fn summary(&self, language: Language) -> Result<GenSummary> {
+        let interface = self.collect_for(language)?;
+        let mut skipped = std::mem::take(&mut *self.skipped.lock().unwrap());
+        skipped.sort();
+        let methods = interface
+            .classes
+            .iter()
+            .map(|it| it.extras.len())
+            .sum::<usize>()
+            + interface
+                .traits
+                .iter()
+                .map(|it| it.extras.len())
+                .sum::<usize>();
+        Ok(GenSummary {
+            classes: interface.classes.len(),
+            methods,
+            enums: interface.enums.len(),
+            traits: interface.traits.len(),
+            skipped,
+        })
+    }
+
+    ///Like [`FileGenerator::collect`] without the methods and constants limited to other
+    /// languages with `only`
+    fn collect_for(&self, language: Language) -> Result<Interface> {
+        let mut interface = self.collect()?;
+        //java methods are also kotlin's since kotlin uses flapigen's java backend
+        let names: &[&str] = match language {
+            Language::Java => &["java"],
+            Language::Kotlin => &["java", "kotlin"],
+            Language::Cpp => &["cpp"],
+            Language::Python => &["python"],
+        };
+        for class in &mut interface.classes {
+            class.extras.retain(|it| {
+                it.only.is_empty() || it.only.iter().any(|it| names.contains(&it.as_str()))
+            });
+        }
+        Ok(interface)
+    }
+
+    ///Scans all the files and merges what was found
+    pub fn collect(&self) -> Result<Interface> {
+        let files = self.source_files()?;
+        //files are read and parsed in parallel then merged in the order they were found so the
+        //output is the same on every run
+        let scanned = files
+            .par_iter()
+            .map(|file_path| self.scan_file(file_path))
+            .collect::<Vec<_>>();
+        let items = || scanned.iter().flatten().flatten();
+        let defined = items().filter_map(|item| match item {
+            ScannedItem::Defined { path, .. } => Some(path.to_vec()),
+            ScannedItem::Type { holder, module, .. } => Some(path_of(module, holder.name())),
+            _ => None,
+        });
+        let used = items().filter_map(|item| match item {
+            ScannedItem::Struct { name, module, .. } => Some(path_of(module, name)),
+            ScannedItem::Method { self_type, .. } => Some(self_type.to_vec()),
+            ScannedItem::Type { holder, module, .. } => Some(path_of(module, holder.name())),
+            ScannedItem::Defined { .. }
+            | ScannedItem::Alias { .. }
+            | ScannedItem::Reexport { .. }
+            | ScannedItem::Import { .. } => None,
+        });
+        let mut known = KnownTypes::new(defined, used);
+        for item in items() {
+            match item {
+                ScannedItem::Reexport { target, name } => {
+                    if let Err(kept) = known.add_reexport(target.to_vec(), name) {
+                        warn!(
+                            "{} is re-exported as both {} and {}, {} is used",
+                            target.join("::"),
+                            kept,
+                            name,
+                            kept
+                        );
+                    }
+                }
+                ScannedItem::Import {
+                    module,
+                    target,
+                    name,
+                } => known.add_import(module, target, name),
+                _ => {}
+            }
+        }
+        //impl blocks of structs that aren't `pub` are skipped with `public_only`
+        let private = items()
+            .filter_map(|item| match item {
+                ScannedItem::Defined { path, public } if self.settings.public_only && !public => {
+                    Some(path.to_vec())
+                }
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        //instantiated generic structs by the name they're known by
+        let mut instantiations = HashMap::new();
+        //where the annotated types are defined by the name they're known by
+        let mut definitions = HashMap::new();
+        //first pass: the kind of every annotated type in all the files, so the signatures resolved
+        // while merging know the types defined in other files
+        for item in items() {
+            match item {
+                ScannedItem::Struct {
+                    name,
+                    module,
+                    instantiate,
+                    ..
+                } => {
+                    let key = known.key(&path_of(module, name));
+                    if !instantiate.is_empty() {
+                        instantiations.insert(key.to_string(), instantiate.to_vec());
+                    }
+                    let definition = known.definition(path_of(module, name));
+                    definitions.insert(key.to_string(), definition);
+                    known.kinds.insert(key, Types::Struct);
+                }
+                ScannedItem::Method { self_type, .. } => {
+                    let key = known.key(self_type);
+                    definitions
+                        .entry(key.to_string())
+                        .or_insert_with(|| known.definition(self_type.to_vec()));
+                    known.kinds.entry(key).or_insert(Types::Struct);
+                }
+                ScannedItem::Type { holder, module, .. } => {
+                    let kind = match holder {
+                        TypeHolder::Trait(_) => Types::Trait,
+                        _ => Types::Enum,
+                    };
+                    let path = path_of(module, holder.name());
+                    definitions.insert(known.key(&path), known.definition(path.to_vec()));
+                    known.kinds.insert(known.key(&path), kind);
+                }
+                ScannedItem::Alias { name, ty } => known.add_alias(name, ty),
+                ScannedItem::Defined { .. }
+                | ScannedItem::Reexport { .. }
+                | ScannedItem::Import { .. } => {}
+            }
+        }
         let mut file_data: HashMap<Rc<String>, TypeHolder> = HashMap::new();
-        let mut closure = |file: &DirEntry| {
-            let file_path = file.path();
-            println!("{}", file_path.display());
-            let file_contents = std::fs::read_to_string(&file_path)
-                .unwrap_or_else(|_| panic!("{}{}", UNABLE_TO_READ, file_path.to_str().unwrap()));
-            let compiled_file = syn::parse_file(&file_contents).expect("Invalid rust file");
-
-            for item in &compiled_file.items {
-                //
+        //structs whose definition has been seen. Impl blocks may be in several files but not the struct
+        let mut defined_structs = HashSet::new();
+        //the supertraits of each trait by the names they're known by
+        let mut supertraits: HashMap<String, Vec<String>> = HashMap::new();
+        //where each method is declared by its type and foreign name, to point at clashes
+        let mut declarations: HashMap<(String, String), String> = HashMap::new();
+        for (file_path, items) in files.into_iter().zip(scanned) {
+            let items = items?;
+            let found = items
+                .iter()
+                .filter(|it| {
+                    !matches!(
+                        it,
+                        ScannedItem::Defined { .. }
+                            | ScannedItem::Alias { .. }
+                            | ScannedItem::Reexport { .. }
+                            | ScannedItem::Import { .. }
+                    )
+                })
+                .count();
+            info!("Scanned {} ({} items)", file_path.display(), found);
+            for item in items {
                 match item {
-                    syn::Item::Struct(item) => {
-                        //check if it has the doc attribute
-                        if has_doc_gen_attr!(item) || has_gen_access_methods_attr(item) {
-                            let name = Rc::new(item.ident.to_string());
-                            //assert!(!file_data.contains_key(&name.clone()));
-                            //the impl block may come (ie if it's in a different file) before the struct definition
-                            if let Some(val) = file_data.get_mut(&name) {
-                                match val {
-                                    TypeHolder::Struct(val) => {
-                                        val.docs.append(&mut get_doc!(item));
+                    ScannedItem::Defined { .. }
+                    | ScannedItem::Alias { .. }
+                    | ScannedItem::Reexport { .. }
+                    | ScannedItem::Import { .. } => {}
+                    ScannedItem::Struct {
+                        name,
+                        mut docs,
+                        location,
+                        module,
+                        deprecated,
+                        ..
+                    } => {
+                        let name = Rc::new(known.key(&path_of(&module, &name)));
+                        if !defined_structs.insert(name.clone()) {
+                            self.skip_unsupported(multiple_definitions(
+                                file_path.to_path_buf(),
+                                location,
+                                &name,
+                            ))?;
+                            continue;
+                        }
+                        //the impl block may come (ie if it's in a different file) before the struct definition
+                        match file_data.get_mut(&name) {
+                            Some(TypeHolder::Struct(val)) => {
+                                val.docs.append(&mut docs);
+                                val.file = Some(file_path.to_path_buf());
+                                val.deprecated = deprecated;
+                            }
+                            Some(_) => {
+                                self.skip_unsupported(GenError::Unsupported {
+                                    file: file_path.to_path_buf(),
+                                    location: Some(location),
+                                    item: name.to_string(),
+                                    reason: "expected a struct".into(),
+                                })?;
+                            }
+                            None => {
+                                let mut data =
+                                    Struct::new(name.to_string(), Types::Struct, docs, vec![]);
+                                data.file = Some(file_path.to_path_buf());
+                                data.deprecated = deprecated;
+                                file_data.insert(name, TypeHolder::Struct(data));
+                            }
+                        }
+                    }
+                    ScannedItem::Method {
+                        self_type,
+                        mut method,
+                        location,
+                        module,
+                        impl_params,
+                    } => {
+                        if private.contains(&known.definition(self_type.to_vec())) {
+                            debug!(
+                                "Skipped {}::{} since {} isn't pub",
+                                self_type.join("::"),
+                                method.signature,
+                                self_type.join("::")
+                            );
+                            continue;
+                        }
+                        let self_type = known.key(&self_type);
+                        if let Err(err) = instantiate(
+                            &file_path,
+                            location,
+                            &self_type,
+                            &impl_params,
+                            instantiations.get(&self_type),
+                            &mut method,
+                        ) {
+                            self.skip_unsupported(err)?;
+                            continue;
+                        }
+                        method.file = Some(file_path.to_path_buf());
+                        method.location = Some(location);
+                        method.implemented_trait = method
+                            .implemented_trait
+                            .take()
+                            .and_then(|it| known.trait_import(&module, &it));
+                        if let Err(err) = self.resolve(
+                            &file_path,
+                            location,
+                            &self_type,
+                            &module,
+                            &mut method,
+                            &known,
+                        ) {
+                            self.skip_unsupported(err)?;
+                            continue;
+                        }
+                        let foreign_name = |it: &ItemInfo| match &it.method_info {
+                            Some(info) if !it.is_constructor => {
+                                Some(info.foreign_name(self.type_case))
+                            }
+                            _ => None,
+                        };
+                        let declared_at = format!("{}:{}", file_path.display(), location);
+                        match file_data.get_mut(&self_type) {
+                            Some(TypeHolder::Struct(val)) => {
+                                let first_constructor = val
+                                    .extras
+                                    .iter()
+                                    .find(|it| it.is_constructor)
+                                    .filter(|_| method.is_constructor);
+                                match (first_constructor, self.settings.constructors) {
+                                    (None, _) | (_, ConstructorPolicy::Overload) => {}
+                                    (Some(_), ConstructorPolicy::Static) => {
+                                        method.is_constructor = false
+                                    }
+                                    (Some(first), ConstructorPolicy::Reject) => {
+                                        let first_at = first
+                                            .file
+                                            .as_ref()
+                                            .zip(first.location)
+                                            .map(|(file, location)| {
+                                                format!(
+                                                    " declared at {}:{}",
+                                                    file.display(),
+                                                    location
+                                                )
+                                            })
+                                            .unwrap_or_default();
+                                        self.skip_unsupported(GenError::Unsupported {
+                                            file: file_path.to_path_buf(),
+                                            location: Some(location),
+                                            item: format!("{}::{}", self_type, method.signature),
+                                            reason: format!(
+                                                "`{}` already has the constructor `{}::{}`{}, \
+                                                 only one is kept with ConstructorPolicy::Reject",
+                                                self_type, self_type, first.signature, first_at
+                                            ),
+                                        })?;
+                                        continue;
                                     }
-                                    _ => {
-                                        panic!("Expected {} to be a struct", name)
+                                }
+                                //renamed methods and methods of different impl blocks may clash
+                                if let Some(name) = foreign_name(&method) {
+                                    let taken = |name: &str| {
+                                        val.extras
+                                            .iter()
+                                            .any(|it| foreign_name(it).as_deref() == Some(name))
+                                    };
+                                    if taken(&name) && self.settings.disambiguate_overloads {
+                                        let suffixed = (1..)
+                                            .map(|i| format!("{}{}", name, i))
+                                            .find(|it| !taken(it))
+                                            .unwrap();
+                                        if let Some(info) = method.method_info.as_mut() {
+                                            info.rename = Some(suffixed);
+                                        }
+                                    } else if taken(&name) {
+                                        let other = val
+                                            .extras
+                                            .iter()
+                                            .find(|it| foreign_name(it).as_ref() == Some(&name))
+                                            .unwrap();
+                                        let other_at = declarations
+                                            .get(&(self_type.to_string(), name.to_string()))
+                                            .map(|it| format!(" declared at {}", it))
+                                            .unwrap_or_default();
+                                        self.skip_unsupported(GenError::Unsupported {
+                                            file: file_path.to_path_buf(),
+                                            location: Some(location),
+                                            item: format!("{}::{}", self_type, method.signature),
+                                            reason: format!(
+                                                "`{}::{}`{} is also called `{}` in the interface, \
+                                                 rename one of them or use \
+                                                 `Generator::disambiguate_overloads`",
+                                                self_type, other.signature, other_at, name
+                                            ),
+                                        })?;
+                                        continue;
+                                    }
+                                }
+                                //constructors are overloads in the foreign class so their
+                                // parameter types must differ, wherever the impl blocks are
+                                let params = |it: &ItemInfo| match &it.method_info {
+                                    Some(info) if it.is_constructor => Some(
+                                        info.args()
+                                            .iter()
+                                            .map(|(_, ty)| ty.to_string())
+                                            .collect::<Vec<_>>(),
+                                    ),
+                                    _ => None,
+                                };
+                                if let Some(types) = params(&method) {
+                                    if let Some(other) = val
+                                        .extras
+                                        .iter()
+                                        .find(|it| params(it).as_ref() == Some(&types))
+                                    {
+                                        self.skip_unsupported(GenError::Unsupported {
+                                            file: file_path.to_path_buf(),
+                                            location: Some(location),
+                                            item: format!("{}::{}", self_type, method.signature),
+                                            reason: format!(
+                                                "the constructor `{}` of `{}` takes the same parameter types",
+                                                other.signature, self_type
+                                            ),
+                                        })?;
+                                        continue;
                                     }
                                 }
-                            } else {
-                                file_data.insert(
-                                    name.clone(),
-                                    TypeHolder::Struct(Struct::new(
-                                        name.to_string(),
-                                        Types::Struct,
-                                        get_doc!(item),
-                                        vec![],
-                                    )),
+                                if let Some(name) = foreign_name(&method) {
+                                    declarations.insert((self_type.to_string(), name), declared_at);
+                                }
+                                val.extras.push(*method)
+                            }
+                            Some(_) => {
+                                self.skip_unsupported(GenError::Unsupported {
+                                    file: file_path.to_path_buf(),
+                                    location: Some(location),
+                                    item: format!("{}::{}", self_type, method.signature),
+                                    reason: "impl functions may only be used for structs".into(),
+                                })?;
+                            }
+                            None => {
+                                //impl block came before struct definition (properly due to the order in which the files
+                                // were read)
+                                //we're assuming the impl method is for a struct
+                                //if it's for an enum, it would crash in the enum function
+                                if let Some(name) = foreign_name(&method) {
+                                    declarations.insert((self_type.to_string(), name), declared_at);
+                                }
+                                let mut data = Struct::new(
+                                    self_type.to_string(),
+                                    Types::Struct,
+                                    vec![],
+                                    vec![*method],
                                 );
+                                data.file = Some(file_path.to_path_buf());
+                                file_data.insert(Rc::new(self_type), TypeHolder::Struct(data));
                             }
                         }
-                        if has_gen_access_methods_attr(item) {
-                            let impl_block = generate_impl_block(item);
-                            FileGenerator::<&Path, &Path>::impl_data(&mut file_data, &impl_block);
-                        }
                     }
-                    syn::Item::Fn(val) => {
-                        // function not in impl block
-                        let name = val.sig.ident.to_string();
-                        if has_gen_attr!(val).is_attribute {
-                            panic!(
-                                "interface functions should be declared in impl blocks.\nName of function {}",
-                                name
-                            )
+                    ScannedItem::Type {
+                        mut holder,
+                        location,
+                        module,
+                        supertraits: parents,
+                    } => {
+                        let name = Rc::new(known.key(&path_of(&module, holder.name())));
+                        holder.set_name(name.to_string());
+                        holder.set_file(file_path.to_path_buf());
+                        for extra in holder.extras_mut() {
+                            extra.file = Some(file_path.to_path_buf());
                         }
-                    }
-                    syn::Item::Impl(val) => {
-                        //TODO let it work with enums
-                        FileGenerator::<&Path, &Path>::impl_data(&mut file_data, val);
-                    }
-                    syn::Item::Enum(val) => {
-                        if has_gen_attr!(val).is_attribute {
-                            let name = Rc::new(val.ident.to_string());
-                            assert!(
-                                !file_data.contains_key(&name),
-                                "Multiple definitions of {}",
-                                &name
-                            ); // make sure no other struct has the same name
-                            let variants = val
-                                .variants
+                        let is_callback = matches!(holder, TypeHolder::Trait(_));
+                        //a callback missing a method couldn't be implemented so the whole type
+                        // is skipped
+                        let resolved = holder.extras_mut().iter_mut().try_for_each(|extra| {
+                            self.resolve(&file_path, location, &name, &module, extra, &known)?;
+                            let output = extra.method_info.as_ref().and_then(|it| it.output());
+                            match (is_callback, output.map(check_callback_output)) {
+                                (true, Some(Err(reason))) => Err(GenError::Unsupported {
+                                    file: file_path.to_path_buf(),
+                                    location: Some(location),
+                                    item: format!("{}::{}", name, extra.signature),
+                                    reason,
+                                }),
+                                _ => Ok(()),
+                            }
+                        });
+                        if let Err(err) = resolved {
+                            self.skip_unsupported(err)?;
+                            continue;
+                        }
+                        // make sure no other struct has the same name
+                        if file_data.contains_key(&name) {
+                            self.skip_unsupported(multiple_definitions(
+                                file_path.to_path_buf(),
+                                location,
+                                &name,
+                            ))?;
+                            continue;
+                        }
+                        if !parents.is_empty() {
+                            let parents = parents
                                 .iter()
-                                .map(|it| ItemInfo::new_enum(it.ident.to_string(), get_doc!(it)))
+                                .map(|it| known.key(&qualify(&module, it)))
                                 .collect();
+                            supertraits.insert(name.to_string(), parents);
+                        }
+                        file_data.insert(name, holder);
+                    }
+                }
+            }
+        }
+        inherit_methods(&mut file_data, &supertraits);
+        if let Some(only) = &self.settings.only_types {
+            retain_types(&mut file_data, only);
+        }
+        let mut interface = Interface::default();
+        for (name, mut type_holder) in file_data {
+            let renamed = match &type_holder {
+                TypeHolder::Trait(val) => val.foreign_name.clone(),
+                _ => None,
+            };
+            let mut foreign = renamed.or_else(|| foreign_name(&name));
+            //the path of the type from the crate root, which needn't be in scope
+            let mut self_type = self
+                .settings
+                .crate_root
+                .as_ref()
+                .zip(definitions.get(name.as_str()))
+                .map(|(root, definition)| format!("{}::{}", root, definition.join("::")));
+            if let Some(types) = instantiations.get(name.as_str()) {
+                let class = foreign.unwrap_or_else(|| name.to_string());
+                foreign = Some(instantiated_name(&class, types));
+                let ty = RustType::Path {
+                    segments: vec![self_type.unwrap_or_else(|| name.to_string())],
+                    args: types.to_vec(),
+                };
+                self_type = Some(ty.to_string());
+            }
+            if let Some(prefix) = &self.settings.class_prefix {
+                foreign = Some(format!(
+                    "{}{}",
+                    prefix,
+                    foreign.unwrap_or_else(|| name.to_string())
+                ));
+            }
+            type_holder.set_foreign_name(foreign);
+            type_holder.set_self_type(self_type);
+            match type_holder {
+                TypeHolder::Struct(val) => interface.classes.push(val),
+                TypeHolder::Trait(val) => interface.traits.push(val),
+                TypeHolder::Enum(val) => interface.enums.push(val),
+            }
+        }
+        interface.classes.sort_by(|a, b| a.name.cmp(&b.name));
+        interface.enums.sort_by(|a, b| a.name.cmp(&b.name));
+        interface.traits.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(interface)
+    }
 
-                            file_data.insert(
-                                name.clone(),
-                                TypeHolder::Enum(Enum::new(
-                                    name.to_string(),
-                                    Types::Enum,
-                                    get_doc!(val),
-                                    variants,
-                                )),
-                            );
+    ///Expands the type aliases in the types of `method`, collapses the error of a returned
+    /// `Result` and applies the type map to them, then drops their lifetimes and resolves their
+    /// paths, `Vec`s and callbacks. `module` is where `method` is declared
+    fn resolve(
+        &self,
+        file_path: &Path,
+        location: Location,
+        self_type: &str,
+        module: &[String],
+        method: &mut ItemInfo,
+        known: &KnownTypes,
+    ) -> Result<()> {
+        let signature = &method.signature;
+        if let Some(method_info) = method.method_info.as_mut() {
+            method.docs.extend(method_info.array_docs());
+            let output = method_info.args().len();
+            for (i, ty) in method_info.types_mut().enumerate() {
+                known
+                    .expand_aliases(ty)
+                    .and_then(|_| {
+                        //after the aliases so `type FooResult = Result<Foo, MyError>` is too
+                        if i == output {
+                            *ty = ty.clone().collapse_result_error();
+                        }
+                        self.type_map.apply(ty);
+                        self.type_map.check(ty)?;
+                        ty.strip_lifetimes();
+                        if self.settings.iterators_as_vec {
+                            ty.iterator_as_vec();
+                        }
+                        if self.settings.paths_as_strings {
+                            ty.paths_as_strings();
+                        }
+                        Ok(())
+                    })
+                    .and_then(|_| known.qualify_type(module, ty))
+                    .and_then(|_| ty.resolve(self_type, &known.kinds))
+                    .map_err(|reason| GenError::Unsupported {
+                        file: file_path.to_path_buf(),
+                        location: Some(location),
+                        item: format!("{}::{}", self_type, signature),
+                        reason,
+                    })?;
+            }
+            //unlike a `Box<dyn Trait>` the foreign object can't be kept by the rust side
+            let borrowed = method_info
+                .args()
+                .iter()
+                .filter(|(_, ty)| ty.is_borrowed_callback(&known.kinds))
+                .map(|(name, _)| {
+                    format!("`{}` is only borrowed for the duration of the call", name)
+                });
+            method.docs.extend(borrowed);
+        }
+        Ok(())
+    }
+
+    ///Every file that would be scanned, in the order they're scanned
+    fn source_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        //folders may be nested in each other but each file is only scanned once
+        let mut seen = HashSet::new();
+        for starting_point in &self.settings.scr_folders {
+            //a single file is read on its own as the crate root
+            if starting_point.is_file() {
+                if !self.settings.is_excluded(starting_point, starting_point)
+                    && seen.insert(starting_point.to_path_buf())
+                {
+                    files.push(starting_point.to_path_buf());
+                }
+                continue;
+            }
+            visit_dirs(starting_point, &mut |file| {
+                let file_path = file.path();
+                if self.settings.has_extension(&file_path)
+                    && !self.settings.is_excluded(starting_point, &file_path)
+                    && seen.insert(file_path.clone())
+                {
+                    files.push(file_path);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(files)
+    }
+
+    ///Describes the settings and the modification time and size of every source file.
+    /// The interface only needs to be generated again when this changes
+    pub fn manifest(&self, language: Language) -> Result<String> {
+        let mut manifest = format!(
+            "rifgen {}\nlanguage {:?}\ntype_case {:?}\nenum_case {:?}\norder {:?}\nconstructors {:?}\nconvert_docs {}\n",
+            env!("CARGO_PKG_VERSION"),
+            language,
+            self.type_case,
+            self.settings.enum_case,
+            self.settings.order,
+            self.settings.constructors,
+            self.settings.convert_docs
+        );
+        let settings = self
+            .settings
+            .scr_folders
+            .iter()
+            .map(|it| format!("folder {}", it.display()))
+            .chain(
+                self.settings
+                    .exclude
+                    .iter()
+                    .map(|it| format!("exclude {}", it)),
+            )
+            .chain(self.type_map.entries().map(|it| format!("type_map {}", it)))
+            .chain(self.settings.cfg.entries().map(|it| format!("cfg {}", it)))
+            .chain(
+                self.settings
+                    .banner
+                    .iter()
+                    .map(|it| format!("banner {:?}", it)),
+            )
+            .chain(self.settings.public_only.then(|| "public_only".to_string()))
+            .chain(
+                self.settings
+                    .strip_doc_comments
+                    .then(|| "strip_doc_comments".to_string()),
+            )
+            .chain(
+                self.settings
+                    .crate_root
+                    .iter()
+                    .map(|it| format!("crate_root {}", it)),
+            )
+            .chain(
+                self.settings
+                    .only_types
+                    .iter()
+                    .flatten()
+                    .map(|it| format!("only {}", it)),
+            )
+            .chain(
+                self.settings
+                    .fail_on_unsupported
+                    .then(|| "fail_on_unsupported".to_string()),
+            )
+            .chain(
+                self.settings
+                    .iterators_as_vec
+                    .then(|| "iterators_as_vec".to_string()),
+            )
+            .chain(
+                self.settings
+                    .paths_as_strings
+                    .then(|| "paths_as_strings".to_string()),
+            )
+            .chain(
+                self.settings
+                    .async_as_blocking
+                    .then(|| "async_as_blocking".to_string()),
+            )
+            .chain(
+                self.settings
+                    .disambiguate_overloads
+                    .then(|| "disambiguate_overloads".to_string()),
+            )
+            .chain(
+                self.settings
+                    .class_prefix
+                    .iter()
+                    .map(|it| format!("class_prefix {}", it)),
+            )
+            .chain(
+                self.settings
+                    .free_fn_class
+                    .iter()
+                    .map(|it| format!("free_fn_class {}", it)),
+            )
+            .chain(
+                self.settings
+                    .symbols
+                    .iter()
+                    .map(|it| format!("symbols {}", it.display())),
+            )
+            .chain(self.settings.append.then(|| "append".to_string()))
+            .chain(Some(format!(
+                "extensions {}",
+                self.settings.extensions.join(" ")
+            )))
+            .chain(Some(format!("indent {:?}", self.settings.indent)))
+            .chain(Some(format!("line_ending {:?}", self.settings.line_ending)))
+            .chain(self.package.iter().map(|it| format!("package {}", it)));
+        for line in settings {
+            manifest.push_str(&line);
+            manifest.push('\n');
+        }
+        for file in self.source_files()? {
+            let metadata = std::fs::metadata(&file)?;
+            let modified = metadata
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            manifest.push_str(&format!(
+                "file {} {} {}\n",
+                file.display(),
+                modified.as_nanos(),
+                metadata.len()
+            ));
+        }
+        Ok(manifest)
+    }
+
+    ///Finds the annotated items in a single file.
+    /// Runs on a worker thread so it can't touch the items found in other files
+    fn scan_file(&self, file_path: &Path) -> Result<Vec<ScannedItem>> {
+        let file_contents = std::fs::read_to_string(file_path)?;
+        let compiled_file = syn::parse_file(&file_contents).map_err(|source| GenError::Parse {
+            file: file_path.to_path_buf(),
+            location: Location::of(source.span()),
+            source,
+        })?;
+        let module = self
+            .settings
+            .scr_folders
+            .iter()
+            .find(|it| file_path.starts_with(it))
+            .map(|it| file_module(it, file_path))
+            .unwrap_or_default();
+        let mut result = Vec::new();
+        self.scan_items(&compiled_file.items, &module, file_path, &mut result)?;
+        Ok(result)
+    }
+
+    ///Scans the items of a file or an inline module. `module` is their path from the crate root
+    fn scan_items(
+        &self,
+        items: &[syn::Item],
+        module: &[String],
+        file_path: &Path,
+        result: &mut Vec<ScannedItem>,
+    ) -> Result<()> {
+        'items: for item in items {
+            if !self.settings.cfg.is_active(item_attrs(item)) {
+                continue;
+            }
+            match item {
+                syn::Item::Mod(val) => {
+                    if let Some((_, items)) = &val.content {
+                        let module = path_of(module, &val.ident.to_string());
+                        self.scan_items(items, &module, file_path, result)?;
+                    }
+                }
+                syn::Item::Struct(item) => {
+                    result.push(ScannedItem::Defined {
+                        path: path_of(module, &item.ident.to_string()),
+                        public: matches!(item.vis, syn::Visibility::Public(_)),
+                    });
+                    if !self.is_visible(&item.vis) {
+                        continue;
+                    }
+                    let struct_args = has_gen_attr!(item).args;
+                    if struct_args.transparent {
+                        //written as its field, which the user converts with a foreign_typemap!
+                        match transparent_field(item) {
+                            Ok(ty) => result.push(ScannedItem::Alias {
+                                name: item.ident.to_string(),
+                                ty: RustType::from_syn(ty),
+                            }),
+                            Err(err) => self.skip_unsupported(GenError::Unsupported {
+                                file: file_path.to_path_buf(),
+                                location: Some(Location::of(err.span())),
+                                item: item.ident.to_string(),
+                                reason: err.to_string(),
+                            })?,
                         }
+                        continue;
                     }
-                    syn::Item::Trait(val) => {
-                        if !has_gen_attr!(val).is_attribute {
+                    let is_value = struct_args.value;
+                    //check if it has the doc attribute
+                    if has_doc_gen_attr!(item) || has_gen_access_methods_attr(item) || is_value {
+                        let args = doc_gen_args(&item.attrs);
+                        if let Err(err) = args.check_instantiations(&item.ident, &item.generics) {
+                            self.skip_unsupported(GenError::Unsupported {
+                                file: file_path.to_path_buf(),
+                                location: Some(Location::of(err.span())),
+                                item: item.ident.to_string(),
+                                reason: err.to_string(),
+                            })?;
                             continue;
                         }
-                        //println!("trait");
-                        let name = Rc::new(val.ident.to_string());
-                        let mut trait_data: Trait = Trait::new(
-                            name.to_string(),
-                            Types::Trait,
-                            get_doc!(val),
-                            Vec::with_capacity(val.items.len()),
-                        );
-                        for item in &val.items {
-                            if let syn::TraitItem::Method(method) = item {
-                                let method_name = method.sig.ident.to_string();
-                                trait_data.extras.push(ItemInfo::new_method(
-                                    function_signature!(method),
-                                    get_doc!(method),
-                                    method_name,
-                                    false,
-                                    types_in_method!(method),
-                                    return_types!(method),
-                                ));
-                            }
+                        let instantiate = item
+                            .generics
+                            .type_params()
+                            .filter_map(|param| {
+                                args.instantiate
+                                    .iter()
+                                    .find(|(name, _)| param.ident == name)
+                            })
+                            .map(|(_, ty)| RustType::from_syn(ty))
+                            .collect();
+                        result.push(ScannedItem::Struct {
+                            name: item.ident.to_string(),
+                            docs: get_doc!(item),
+                            deprecated: deprecation(&item.attrs),
+                            location: Location::of(item.ident.span()),
+                            module: module.to_vec(),
+                            instantiate,
+                        });
+                    }
+                    if has_gen_access_methods_attr(item) {
+                        let impl_block = generate_impl_block(item);
+                        self.impl_data(result, &impl_block, module, file_path)?;
+                    }
+                    if is_value {
+                        let (impl_block, skipped) = generate_value_accessors(item);
+                        for field in skipped {
+                            self.skip_unsupported(GenError::Unsupported {
+                                file: file_path.to_path_buf(),
+                                location: Some(Location::of(field.ty.span())),
+                                item: format!(
+                                    "{}::{}",
+                                    item.ident,
+                                    field.ident.map(|it| it.to_string()).unwrap_or_default()
+                                ),
+                                reason: "fields of value structs should be primitives or String"
+                                    .into(),
+                            })?;
                         }
-                        assert!(
-                            !file_data.contains_key(&name),
-                            "Multiple definitions of {}",
-                            &name
-                        ); // make sure no other struct has the same name
-                        file_data.insert(name.clone(), TypeHolder::Trait(trait_data));
+                        self.impl_data(result, &impl_block, module, file_path)?;
                     }
-                    _ => {
-                        //todo
+                }
+                syn::Item::Use(val) => {
+                    imports(module, &val.tree, &mut vec![], result);
+                    if matches!(val.vis, syn::Visibility::Public(_)) {
+                        reexports(module, &val.tree, &mut vec![], result);
                     }
                 }
-            }
-        };
-        visit_dirs(&self.starting_point, &mut closure).expect("Unable to read directory");
-        //create interface file
-        let mut holder = ItemsHolder::new(file_data.len());
-        //file_data.iter().for_each(|it| println!("it {:?}", it));
-        for (name, type_holder) in file_data {
-            match type_holder {
-                TypeHolder::Struct(_) | TypeHolder::Trait(_) => {
-                    holder.add_items(name, type_holder);
+                syn::Item::Type(val) if val.generics.params.is_empty() => {
+                    result.push(ScannedItem::Alias {
+                        name: val.ident.to_string(),
+                        ty: RustType::from_syn(&val.ty),
+                    });
+                }
+                syn::Item::Fn(val) => {
+                    // function not in impl block
+                    let name = val.sig.ident.to_string();
+                    let fn_info = has_gen_attr!(val);
+                    if !fn_info.is_attribute || fn_info.args.ignore || !self.is_visible(&val.vis) {
+                        continue;
+                    }
+                    let location = Location::of(val.sig.ident.span());
+                    let class = match &self.settings.free_fn_class {
+                        Some(class) if !fn_info.args.constructor => class,
+                        _ => {
+                            self.skip_unsupported(GenError::Unsupported {
+                                file: file_path.to_path_buf(),
+                                location: Some(location),
+                                item: name,
+                                reason: "free functions are only static methods of the class of \
+                                         Generator::free_fn_class"
+                                    .into(),
+                            })?;
+                            continue;
+                        }
+                    };
+                    let is_async = val.sig.asyncness.is_some();
+                    if is_async && !self.settings.async_as_blocking {
+                        self.skip_unsupported(GenError::Unsupported {
+                            file: file_path.to_path_buf(),
+                            location: Some(location),
+                            item: name,
+                            reason: "async functions need Generator::async_as_blocking".into(),
+                        })?;
+                        continue;
+                    }
+                    if let Err(err) = fn_info.args.check_signature(&val.sig) {
+                        self.skip_unsupported(GenError::Unsupported {
+                            file: file_path.to_path_buf(),
+                            location: Some(Location::of(err.span())),
+                            item: name,
+                            reason: err.to_string(),
+                        })?;
+                        continue;
+                    }
+                    let mut info = method_info!(val);
+                    let generics = generic_types(&fn_info.args);
+                    info.types_mut().for_each(|it| generics.apply(it));
+                    info.skip_args(&fn_info.args.skip_args);
+                    info.rename = fn_info.args.name.clone();
+                    let mut method = ItemInfo::new_method(get_doc!(val), false, info);
+                    method.deprecated = deprecation(&val.attrs);
+                    method.only = fn_info.args.only.clone();
+                    method.function_path = Some(module.join("::"));
+                    if is_async {
+                        method
+                            .docs
+                            .push("Blocks until the rust future completes".to_string());
+                    }
+                    result.push(ScannedItem::Method {
+                        self_type: vec![class.to_string()],
+                        module: module.to_vec(),
+                        location,
+                        method: Box::new(method),
+                        impl_params: vec![],
+                    });
+                }
+                syn::Item::Impl(val) => {
+                    //TODO let it work with enums
+                    self.impl_data(result, val, module, file_path)?;
+                }
+                syn::Item::Enum(val)
+                    if (has_gen_attr!(val).is_attribute || has_doc_gen_attr!(val))
+                        && self.is_visible(&val.vis) =>
+                {
+                    let mut variants = Vec::with_capacity(val.variants.len());
+                    //variants after one with a discriminant are numbered from it
+                    let mut next = val
+                        .variants
+                        .iter()
+                        .any(|it| it.discriminant.is_some())
+                        .then_some(0);
+                    for variant in val
+                        .variants
+                        .iter()
+                        .filter(|it| self.settings.cfg.is_active(&it.attrs))
+                    {
+                        let checked =
+                            check_unit_variant(variant).and_then(|_| discriminant(variant));
+                        let value = match checked {
+                            Ok(value) => value.or(next),
+                            //the variants of the foreign enum must match the rust enum
+                            Err(reason) => {
+                                self.skip_unsupported(GenError::Unsupported {
+                                    file: file_path.to_path_buf(),
+                                    location: Some(Location::of(variant.ident.span())),
+                                    item: format!("{}::{}", val.ident, variant.ident),
+                                    reason,
+                                })?;
+                                continue 'items;
+                            }
+                        };
+                        next = value.map(|it| it + 1);
+                        let mut info =
+                            ItemInfo::new_enum(variant.ident.to_string(), get_doc!(variant));
+                        info.discriminant = value;
+                        info.deprecated = deprecation(&variant.attrs);
+                        variants.push(info);
+                    }
+                    let mut data =
+                        Enum::new(val.ident.to_string(), Types::Enum, get_doc!(val), variants);
+                    data.deprecated = deprecation(&val.attrs);
+                    result.push(ScannedItem::Type {
+                        holder: TypeHolder::Enum(data),
+                        location: Location::of(val.ident.span()),
+                        module: module.to_vec(),
+                        supertraits: vec![],
+                    });
+                }
+                syn::Item::Trait(val) => {
+                    //the traits of impl blocks are imported from where they're defined
+                    result.push(ScannedItem::Defined {
+                        path: path_of(module, &val.ident.to_string()),
+                        public: matches!(val.vis, syn::Visibility::Public(_)),
+                    });
+                    let attr_check: AttrCheck = has_gen_attr!(val);
+                    let is_annotated = attr_check.is_attribute || has_doc_gen_attr!(val);
+                    if !is_annotated || !self.is_visible(&val.vis) {
+                        continue;
+                    }
+                    let mut trait_data: Trait = Trait::new(
+                        val.ident.to_string(),
+                        Types::Trait,
+                        get_doc!(val),
+                        Vec::with_capacity(val.items.len()),
+                    );
+                    //the foreign interface may be renamed, it's still passed as `Box<dyn Trait>`
+                    trait_data.foreign_name = attr_check.args.name;
+                    trait_data.deprecated = deprecation(&val.attrs);
+                    for item in &val.items {
+                        let method = match item {
+                            syn::TraitItem::Method(method) => method,
+                            other => {
+                                warn!(
+                                    "Skipped `{}` of callback {} in {}: callbacks may only have methods",
+                                    other.to_token_stream(),
+                                    val.ident,
+                                    file_path.display()
+                                );
+                                continue;
+                            }
+                        };
+                        if !self.settings.cfg.is_active(&method.attrs)
+                            || has_gen_attr!(method).args.ignore
+                        {
+                            continue;
+                        }
+                        //the foreign implementation is only called through `dyn Trait`
+                        let is_generic = method
+                            .sig
+                            .generics
+                            .params
+                            .iter()
+                            .any(|it| !matches!(it, syn::GenericParam::Lifetime(_)));
+                        if is_generic || requires_sized(&method.sig) {
+                            self.skip_unsupported(GenError::Unsupported {
+                                file: file_path.to_path_buf(),
+                                location: Some(Location::of(method.sig.ident.span())),
+                                item: format!("{}::{}", val.ident, method.sig.ident),
+                                reason: if is_generic {
+                                    "generic methods can't be called on `dyn Trait`"
+                                } else {
+                                    "methods bounded by `Self: Sized` can't be called on `dyn Trait`"
+                                }
+                                .into(),
+                            })?;
+                            continue;
+                        }
+                        let mut info =
+                            ItemInfo::new_method(get_doc!(method), false, method_info!(method));
+                        info.deprecated = deprecation(&method.attrs);
+                        info.location = Some(Location::of(method.sig.ident.span()));
+                        trait_data.extras.push(info);
+                    }
+                    let supertraits = val
+                        .supertraits
+                        .iter()
+                        .filter_map(|it| match it {
+                            syn::TypeParamBound::Trait(bound) => Some(
+                                bound
+                                    .path
+                                    .segments
+                                    .iter()
+                                    .map(|it| it.ident.to_string())
+                                    .collect(),
+                            ),
+                            syn::TypeParamBound::Lifetime(_) => None,
+                        })
+                        .collect();
+                    result.push(ScannedItem::Type {
+                        holder: TypeHolder::Trait(trait_data),
+                        location: Location::of(val.ident.span()),
+                        module: module.to_vec(),
+                        supertraits,
+                    });
+                }
+                _ => {
+                    //todo
                 }
-                TypeHolder::Enum(val) => holder.add_enum(val),
             }
         }
-        holder.generate_interface(language, &self.interface_file_path);
-        println!("Total Time Taken To Generate File {:?}", start.elapsed());
+        Ok(())
     }
 
-    fn impl_data(map: &mut HashMap<Rc<String>, TypeHolder>, item: &syn::ItemImpl) {
+    ///Whether an item with `vis` is generated. Only `pub` items are with `public_only`
+    fn is_visible(&self, vis: &syn::Visibility) -> bool {
+        !self.settings.public_only || matches!(vis, syn::Visibility::Public(_))
+    }
+
+    fn impl_data(
+        &self,
+        result: &mut Vec<ScannedItem>,
+        item: &syn::ItemImpl,
+        module: &[String],
+        file_path: &Path,
+    ) -> Result<()> {
         let self_type = &*item.self_ty;
         if let syn::Type::Path(type_path) = self_type {
-            let name = type_path
+            //`impl model::Foo` and `impl<T> Foo<T>` are both for `Foo`
+            let segments = type_path
                 .path
                 .segments
                 .iter()
-                .next()
-                .map(|it| it.ident.to_string());
+                .map(|it| it.ident.to_string())
+                .collect::<Vec<_>>();
+            let path = qualify(module, &segments);
+            let name = segments.last();
+            let type_args = match type_path.path.segments.last().map(|it| &it.arguments) {
+                Some(syn::PathArguments::AngleBracketed(args)) => args
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+            let impl_params = item
+                .generics
+                .type_params()
+                .map(|param| {
+                    let position = type_args.iter().position(
+                        |ty| matches!(ty, syn::Type::Path(ty) if ty.path.is_ident(&param.ident)),
+                    );
+                    (param.ident.to_string(), position)
+                })
+                .collect::<Vec<_>>();
+            //methods of trait impls are as visible as the trait
+            let implemented_trait = item.trait_.as_ref().map(|(_, path, _)| {
+                path.segments
+                    .iter()
+                    .map(|it| it.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::")
+            });
+            let is_visible = |vis| implemented_trait.is_some() || self.is_visible(vis);
             if let Some(name) = name {
                 //name of struct or enum
                 for item in item.items.iter() {
+                    if let syn::ImplItem::Const(constant) = item {
+                        let const_info: AttrCheck = has_gen_attr!(constant);
+                        if !const_info.is_attribute
+                            || const_info.args.ignore
+                            || !self.settings.cfg.is_active(&constant.attrs)
+                            || !is_visible(&constant.vis)
+                        {
+                            continue;
+                        }
+                        let location = Location::of(constant.ident.span());
+                        let ty = match constant_type(constant) {
+                            Ok(ty) => ty,
+                            Err(reason) => {
+                                self.skip_unsupported(GenError::Unsupported {
+                                    file: file_path.to_path_buf(),
+                                    location: Some(location),
+                                    item: format!("{}::{}", name, constant.ident),
+                                    reason,
+                                })?;
+                                continue;
+                            }
+                        };
+                        let mut info =
+                            MethodInfo::new(constant.ident.to_string(), None, vec![], Some(ty));
+                        info.rename = const_info.args.name.clone();
+                        info.constant = Some(constant.ident.to_string());
+                        let mut method = ItemInfo::new_method(get_doc!(constant), false, info);
+                        method.implemented_trait = implemented_trait.clone();
+                        method.deprecated = deprecation(&constant.attrs);
+                        method.only = const_info.args.only.clone();
+                        result.push(ScannedItem::Method {
+                            self_type: path.to_vec(),
+                            module: module.to_vec(),
+                            location,
+                            method: Box::new(method),
+                            impl_params: impl_params.clone(),
+                        });
+                    }
                     if let syn::ImplItem::Method(method) = item {
-                        let method_info: AttrCheck = has_gen_attr!(method, true);
+                        let method_info: AttrCheck = has_gen_attr!(method);
                         //not supporting enums for now
-                        if !method_info.is_attribute {
+                        if !method_info.is_attribute
+                            || method_info.args.ignore
+                            || !self.settings.cfg.is_active(&method.attrs)
+                            || !is_visible(&method.vis)
+                        {
+                            continue;
+                        }
+                        if method_info.args.is_static && method.sig.receiver().is_some() {
+                            self.skip_unsupported(GenError::Unsupported {
+                                file: file_path.to_path_buf(),
+                                location: Some(Location::of(method.sig.ident.span())),
+                                item: format!("{}::{}", name, method.sig.ident),
+                                reason: "static methods can't take a self receiver".into(),
+                            })?;
+                            continue;
+                        }
+                        let is_async = method.sig.asyncness.is_some();
+                        if is_async && !self.settings.async_as_blocking {
+                            self.skip_unsupported(GenError::Unsupported {
+                                file: file_path.to_path_buf(),
+                                location: Some(Location::of(method.sig.ident.span())),
+                                item: format!("{}::{}", name, method.sig.ident),
+                                reason: "async methods need Generator::async_as_blocking".into(),
+                            })?;
                             continue;
                         }
-                        let method_name = method.sig.ident.to_string();
-                        let data = map.get_mut(&name);
-                        let item_info = ItemInfo::new_method(
-                            function_signature!(method),
+                        if let Err(err) = method_info.args.check_signature(&method.sig) {
+                            self.skip_unsupported(GenError::Unsupported {
+                                file: file_path.to_path_buf(),
+                                location: Some(Location::of(err.span())),
+                                item: format!("{}::{}", name, method.sig.ident),
+                                reason: err.to_string(),
+                            })?;
+                            continue;
+                        }
+                        let mut info = method_info!(method);
+                        let generics = generic_types(&method_info.args);
+                        info.types_mut().for_each(|it| generics.apply(it));
+                        info.skip_args(&method_info.args.skip_args);
+                        info.rename = method_info.args.name.clone();
+                        let location = Location::of(method.sig.ident.span());
+                        let deprecated = deprecation(&method.attrs);
+                        let mut method = ItemInfo::new_method(
                             get_doc!(method),
-                            method_name,
-                            method_info.is_constructor,
-                            types_in_method!(method),
-                            return_types!(method),
+                            method_info.args.constructor,
+                            info,
                         );
-                        if let Some(data) = data {
-                            match data {
-                                TypeHolder::Struct(val) => {
-                                    val.extras.push(item_info);
-                                }
-                                _ => {
-                                    unimplemented!("Impl function may only be used for structs")
-                                }
-                            }
-                        } else {
-                            //impl block came before struct definition (properly due to the order in which the files
-                            // were read)
-                            //we're assuming the impl method is for a struct
-                            //if it's for an enum, it would crash in the enum function
-                            let data = Struct::new(
-                                name.to_string(),
-                                Types::Struct,
-                                vec![],
-                                vec![item_info],
-                            );
-                            map.insert(Rc::new(name.clone()), TypeHolder::Struct(data));
+                        method.implemented_trait = implemented_trait.clone();
+                        method.deprecated = deprecated;
+                        method.only = method_info.args.only.clone();
+                        if is_async {
+                            //the declared output is that of the future
+                            method
+                                .docs
+                                .push("Blocks until the rust future completes".to_string());
                         }
+                        result.push(ScannedItem::Method {
+                            self_type: path.to_vec(),
+                            module: module.to_vec(),
+                            location,
+                            method: Box::new(method),
+                            impl_params: impl_params.clone(),
+                        });
                     }
                 }
             }
         };
+        Ok(())
     }
 }
 
-/*
-todo!()
-#[cfg(test)]
-mod tests {
-    use crate::generator_lib::AttrCheck;
-    use syn::__private::ToTokens;
-    use syn::{Item, PathArguments, ReturnType, Type};
-
-    #[test]
-    fn test_syn() {
-        let item = syn::parse_str::<syn::Item>(
-            "
-#[generate_interface_doc]
-#[derive(Debug)]
-///struct us good
-///
-struct Kofi {
-    kkkk: i64,
+///What a single file contributes to the interface. `module` is the path of the module the
+/// item is in
+enum ScannedItem {
+    ///Any struct, to tell which module the types of impl blocks are from
+    Defined { path: Vec<String>, public: bool },
+    ///`type name = ty;`, which is expanded wherever it's used in a signature
+    Alias { name: String, ty: RustType },
+    ///`pub use target as name;` where `target` is the path from the crate root
+    Reexport { target: Vec<String>, name: String },
+    ///`use target as name;` or `use target;`, which brings `name` into scope in `module`
+    Import {
+        module: Vec<String>,
+        target: Vec<String>,
+        name: String,
+    },
+    ///Definition of a struct annotated with `#[generate_interface_doc]`
+    Struct {
+        name: String,
+        docs: Vec<String>,
+        location: Location,
+        module: Vec<String>,
+        ///the types the type parameters of a generic struct are instantiated with, in the
+        /// order of the parameters
+        instantiate: Vec<RustType>,
+        ///the note of `#[deprecated]`
+        deprecated: Option<String>,
+    },
+    ///An annotated method in an impl block
+    Method {
+        ///path of the type from the crate root
+        self_type: Vec<String>,
+        method: Box<ItemInfo>,
+        location: Location,
+        module: Vec<String>,
+        ///the type parameters of the impl block and where they are among the type arguments
+        /// of its type ie `T` of `impl<T> Wrapper<T>` is the first
+        impl_params: Vec<(String, Option<usize>)>,
+    },
+    ///An annotated enum or trait
+    Type {
+        holder: TypeHolder,
+        location: Location,
+        module: Vec<String>,
+        ///the paths of the supertraits of a trait as they're written
+        supertraits: Vec<Vec<String>>,
+    },
 }
-",
-        )
-        .unwrap();
-        ////println!("{:#?}", item);
-        let item = syn::parse_str::<syn::Item>(
-            "#[generate_interface(here)]
-    ///nice docs
-    fn not() {
-        unimplemented!()
-    }",
-        )
-        .unwrap();
-        //println!("{:#?}", item);
-
-        if let syn::Item::Fn(val) = item {
-            let y = val
-                .attrs
-                .iter()
-                .map(|it| it.tokens.to_string())
-                //.filter(|it| it.contains("constructor"))
-                .collect::<Vec<String>>();
-            val.attrs.iter().any(|it| {
-                let is_attribute = it
-                    .path
-                    .segments
-                    .iter()
-                    .any(|it| it.ident == "generate_interface");
-                //println!("any {}", is_attribute);
-                is_attribute
-            });
-            for attribute in val.attrs.iter() {
-                let is_attribute = attribute
-                    .path
-                    .segments
-                    .iter()
-                    .any(|it| it.ident == "generate_interface");
-                //println!("is {}", is_attribute);
-            }
-            //println!("vec {:?} has {:?}", y, has_gen_attr!(val, true));
-        }
-        panic!()
-    }
-
-    #[test]
-    fn test_types_in_method() {
-        let item = syn::parse_str::<Item>("fn others(h: Box<Box<i64,FOK>>, j: OTH) {}").unwrap();
-        let item1 = syn::parse_str::<Item>(
-            "unsafe fn new(kkkk: i64, k: Trial) -> HashMap<i64,Kofi> {
-        Kofi { kkkk }
-    }",
-        )
-        .unwrap();
-        let item2 = syn::parse_str::<Item>(
-            "unsafe fn new(kkkk: i64, k: Trial) -> Kofi {
-        Kofi { kkkk }
-    }",
-        )
-        .unwrap();
-        if let syn::Item::Fn(val) = item1 {
-            let vecs = types_in_method!(val);
-            ////println!("types in {:?} {:?}", vecs, return_types!(val));
-            match val.sig.output {
-                ReturnType::Default => {}
-                ReturnType::Type(_, val) => match &*val {
-                    Type::Path(val) => {
-                        //let result = vec![];
-                        val.path.segments.iter().for_each(|it| {
-                            //println!("ident {}", &it.ident);
-                            match &it.arguments {
-                                PathArguments::None => {}
-                                PathArguments::AngleBracketed(val) => {
-                                    let str = val.args.to_token_stream().to_string();
-                                    val.args.iter().for_each(|it| {
-                                        let str = it.to_token_stream().to_string();
-                                        //println!("str {}", str);
-                                    });
-                                }
-                                PathArguments::Parenthesized(_) => {}
-                            }
-                        });
-                    }
-                    _ => {}
-                },
+
+///The receiver of a method with a typed `self` ie `self: &Self` is `&self` while
+/// `self: Arc<Self>`, `self: Rc<Self>` and `self: Box<Self>` are kept as they are
+fn self_receiver(ty: RustType) -> String {
+    match &ty {
+        RustType::Reference { mutable, inner, .. }
+            if inner.name().is_some_and(|it| it == "Self") =>
+        {
+            if *mutable { "&mut self" } else { "&self" }.to_string()
+        }
+        _ if ty.name().is_some_and(|it| it == "Self") => "self".to_string(),
+        _ => format!("self: {}", ty),
+    }
+}
+
+///Replaces the type parameters of the impl block of `method` with the types its struct is
+/// instantiated with, `instantiated` when it's a generic struct. `Self` becomes the
+/// instantiated type
+fn instantiate(
+    file_path: &Path,
+    location: Location,
+    self_type: &str,
+    impl_params: &[(String, Option<usize>)],
+    instantiated: Option<&Vec<RustType>>,
+    method: &mut ItemInfo,
+) -> Result<()> {
+    let path = |name: &str, args: Vec<RustType>| RustType::Path {
+        segments: vec![name.to_string()],
+        args,
+    };
+    let mut types = TypeMap::default();
+    let mut uninstantiated = Vec::new();
+    for (param, position) in impl_params {
+        match position.and_then(|i| instantiated?.get(i)) {
+            Some(ty) => types.insert(path(param, vec![]), ty.clone()),
+            None => uninstantiated.push(param),
+        }
+    }
+    if let Some(instantiated) = instantiated {
+        types.insert(path("Self", vec![]), path(self_type, instantiated.to_vec()));
+    }
+    let method_info = match method.method_info.as_mut() {
+        Some(method_info) => method_info,
+        None => return Ok(()),
+    };
+    method_info.types_mut().for_each(|it| types.apply(it));
+    let referenced = method_info
+        .types_mut()
+        .flat_map(|it| it.names())
+        .find(|it| uninstantiated.contains(&it));
+    match referenced {
+        Some(param) => Err(GenError::Unsupported {
+            file: file_path.to_path_buf(),
+            location: Some(location),
+            item: format!("{}::{}", self_type, method.signature),
+            reason: format!(
+                "`{}` is a type parameter of the impl block, instantiate the struct with it \
+                 ie #[generate_interface_doc(instantiate({} = \"String\"))]",
+                param, param
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+///Keeps the types called any of `only` and the types their signatures use, so the interface
+/// stays valid. The types that weren't asked for are logged
+fn retain_types(types: &mut HashMap<Rc<String>, TypeHolder>, only: &[String]) {
+    let mut pending = Vec::with_capacity(only.len());
+    for name in only {
+        match types.get_key_value(name) {
+            Some((key, _)) => pending.push(key.clone()),
+            None => warn!("There's no annotated type called {}", name),
+        }
+    }
+    let mut kept = HashSet::new();
+    while let Some(name) = pending.pop() {
+        if !kept.insert(name.clone()) {
+            continue;
+        }
+        //types are known by their name unless it's ambiguous
+        for ty in types[&name].types() {
+            let short = ty.rsplit("::").next().unwrap_or_default().to_string();
+            if let Some((key, _)) = types
+                .get_key_value(&ty)
+                .or_else(|| types.get_key_value(&short))
+            {
+                pending.push(key.clone());
             }
         }
     }
+    let mut added = kept
+        .iter()
+        .filter(|it| !only.contains(it))
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>();
+    if !added.is_empty() {
+        added.sort_unstable();
+        warn!(
+            "Also generated {} since the types asked for use them",
+            added.join(", ")
+        );
+    }
+    types.retain(|name, _| kept.contains(name));
+}
+
+fn path_of(module: &[String], name: &str) -> Vec<String> {
+    let mut path = module.to_vec();
+    path.push(name.to_string());
+    path
+}
+
+///The file names used by `FileGenerator::build_split` for `names`, in the same order
+fn split_file_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let names = names.collect::<Vec<_>>();
+    let mut sorted = names.clone();
+    sorted.sort_unstable();
+    let mut taken = [SPLIT_HEADER, SPLIT_INDEX]
+        .iter()
+        .map(|it| it.to_lowercase())
+        .collect::<HashSet<_>>();
+    let mut file_names = HashMap::with_capacity(names.len());
+    for name in sorted {
+        let mut file_name = format!("{}.in", name);
+        let mut suffix = 1;
+        while !taken.insert(file_name.to_lowercase()) {
+            suffix += 1;
+            file_name = format!("{}_{}.in", name, suffix);
+        }
+        file_names.insert(name, file_name);
+    }
+    names.iter().map(|it| file_names[it].to_string()).collect()
+}
+
+fn item_attrs(item: &syn::Item) -> &[Attribute] {
+    match item {
+        syn::Item::Struct(val) => &val.attrs,
+        syn::Item::Fn(val) => &val.attrs,
+        syn::Item::Impl(val) => &val.attrs,
+        syn::Item::Enum(val) => &val.attrs,
+        syn::Item::Trait(val) => &val.attrs,
+        syn::Item::Mod(val) => &val.attrs,
+        syn::Item::Type(val) => &val.attrs,
+        syn::Item::Use(val) => &val.attrs,
+        _ => &[],
+    }
+}
+
+///Adds the renamed items of `pub use tree;` in `module` ie `InnerFoo` `as Foo`. `prefix` is
+/// the path before `tree`
+fn reexports(
+    module: &[String],
+    tree: &syn::UseTree,
+    prefix: &mut Vec<String>,
+    result: &mut Vec<ScannedItem>,
+) {
+    match tree {
+        syn::UseTree::Path(val) => {
+            prefix.push(val.ident.to_string());
+            reexports(module, &val.tree, prefix, result);
+            prefix.pop();
+        }
+        syn::UseTree::Rename(val) if val.ident != val.rename => {
+            prefix.push(val.ident.to_string());
+            result.push(ScannedItem::Reexport {
+                target: qualify(module, prefix),
+                name: val.rename.to_string(),
+            });
+            prefix.pop();
+        }
+        syn::UseTree::Group(val) => val
+            .items
+            .iter()
+            .for_each(|it| reexports(module, it, prefix, result)),
+        _ => {}
+    }
+}
+
+///Adds each name `tree` brings into scope in `module`, glob imports aside
+fn imports(
+    module: &[String],
+    tree: &syn::UseTree,
+    prefix: &mut Vec<String>,
+    result: &mut Vec<ScannedItem>,
+) {
+    let (ident, name) = match tree {
+        syn::UseTree::Path(val) => {
+            prefix.push(val.ident.to_string());
+            imports(module, &val.tree, prefix, result);
+            prefix.pop();
+            return;
+        }
+        syn::UseTree::Group(val) => {
+            val.items
+                .iter()
+                .for_each(|it| imports(module, it, prefix, result));
+            return;
+        }
+        syn::UseTree::Name(val) if val.ident != "self" => (&val.ident, &val.ident),
+        syn::UseTree::Rename(val) if val.ident != "self" => (&val.ident, &val.rename),
+        _ => return,
+    };
+    prefix.push(ident.to_string());
+    result.push(ScannedItem::Import {
+        module: module.to_vec(),
+        target: qualify(module, prefix),
+        name: name.to_string(),
+    });
+    prefix.pop();
+}
+
+///The type of the getter of an associated constant. Only integer, `bool`, `char`, `&str` and
+/// `&[u8]` constants set to a literal are supported
+fn constant_type(constant: &syn::ImplItemConst) -> std::result::Result<RustType, String> {
+    let ty = RustType::from_syn(&constant.ty);
+    let mut value = &constant.expr;
+    if let syn::Expr::Unary(syn::ExprUnary {
+        op: syn::UnOp::Neg(_),
+        expr,
+        ..
+    }) = value
+    {
+        value = expr;
+    }
+    let literal = match value {
+        syn::Expr::Lit(val) => &val.lit,
+        _ => {
+            return Err(format!(
+                "`{}` must be set to a literal, computed values aren't supported",
+                constant.ident
+            ))
+        }
+    };
+    let is_bytes = |ty: &RustType| match ty {
+        RustType::Slice(inner) | RustType::Array(inner, _) => {
+            inner.name().is_some_and(|it| it == "u8")
+        }
+        _ => false,
+    };
+    match (literal, &ty) {
+        (syn::Lit::ByteStr(_), RustType::Reference { inner, .. }) if is_bytes(inner) => {
+            //`&'static [u8; 4]` is returned as `&[u8]`
+            Ok(RustType::from_syn(&syn::parse_quote!(&[u8])))
+        }
+        (syn::Lit::Char(_), _) if ty.name().is_some_and(|it| it == "char") => Ok(ty),
+        (syn::Lit::Byte(_), _) if ty.name().is_some_and(|it| it == "u8") => Ok(ty),
+        (syn::Lit::Str(_), RustType::Reference { inner, .. })
+            if inner.name().is_some_and(|it| it == "str") =>
+        {
+            //`&'static str` is returned as `&str`
+            Ok(RustType::from_syn(&syn::parse_quote!(&str)))
+        }
+        (syn::Lit::Int(_), _) | (syn::Lit::Bool(_), _)
+            if ty
+                .name()
+                .is_some_and(|it| it != "f32" && it != "f64" && is_primitive(it)) =>
+        {
+            Ok(ty)
+        }
+        _ => Err(format!(
+            "constants of type `{}` aren't supported, only integers, `bool`, `char`, `&str` \
+             and `&[u8]`",
+            ty
+        )),
+    }
+}
+
+fn multiple_definitions(file: PathBuf, location: Location, name: &str) -> GenError {
+    GenError::Unsupported {
+        file,
+        location: Some(location),
+        item: name.to_string(),
+        reason: "multiple definitions of this type".into(),
+    }
 }
-*/
@@ -55,12 +55,24 @@
 //!```no_run
 //! //place this code before flapigen swig_expand function
 //! use rifgen::{Generator, TypeCases, Language};
+//! # fn main() -> Result<(), rifgen::GenError> {
 //! let source_folder = "/user/projects"; //use your projects folder
 //! let out_file = "/user/projects/glue.in";
 //! Generator::new(TypeCases::CamelCase,Language::Java,source_folder)
-//! .generate_interface(out_file)
+//! .generate_interface(out_file)?;
+//! # Ok(())
+//! # }
 //! ```
 //!
+//! `generate_interface` returns a [`GenError`] naming the offending file when a source file can't be
+//! read or parsed. Use `generate_interface_or_panic` to keep the old panicking behaviour.
+//! Annotated items that can't be expressed in the interface file are skipped with a warning
+//! unless [`Generator::fail_on_unsupported`] is set
+//!
+//! Progress is reported through the [`log`](https://docs.rs/log) crate: each scanned file with
+//! the number of items found at `info` level, each emitted type and method at `debug` level and
+//! skipped items at `warn` level. Nothing is printed unless a logger is installed
+//!
 //! Using the example above, the modified code would be
 //! ```
 //! use rifgen::rifgen_attr::*;
@@ -87,8 +99,145 @@
 //! }
 //! ```
 //!
-//! This crate works with doc comments so all doc comments would be preserved
-//! Use `#[generate_interface_doc]` on <b>structs only</b> to preserve the doc comment of the struct
+//! Parameter and return types are written as they're declared, so `&str` stays `&str` and
+//! flapigen passes it without copying while `String` is passed by value. That holds inside
+//! `Option` too ie `Option<&str>`, but `Vec`s must hold owned values ie `Vec<String>`.
+//! Lifetimes are left out so `fn data<'a>(&'a self) -> &'a [u8]` is written like
+//! `fn data(&self) -> &[u8]`
+//!
+//! Raw identifiers lose their `r#` in the foreign names ie `fn r#match(&self)` is written
+//! `fn Foo::r#match(&self); alias match;`. Parameter names are part of the rust signature so
+//! those that are keywords stay raw ie `r#type: i32`
+//!
+//! Annotated items marked `#[deprecated]` end their doc comment with `@deprecated` for java and
+//! kotlin and with Doxygen's `\\deprecated` for C++, followed by the note when there's one.
+//! flapigen doesn't write annotations from the interface file so that's what the foreign side
+//! gets, which javac and IDEs pick up
+//!
+//! Constructors may fail by returning `Result<Self, E>` or `Result<Foo, E>`. Like other methods
+//! returning a `Result`, the error becomes a `String` which flapigen throws as an exception
+//! ie `constructor Foo::open(path: &str) -> Result<Foo, String>;`
+//!
+//! Only methods marked `#[generate_interface(constructor)]` are constructors, whatever their
+//! name or return type. Methods without a `self` receiver are emitted as static methods, so is
+//! `fn new() -> Foo` when it's only marked `#[generate_interface]`. Receivers are written as
+//! declared so flapigen borrows or consumes the object the same way, `&self`, `&mut self` and
+//! `self`, which `mut self` also is.
+//! Use `#[generate_interface(static)]` to make the intent explicit
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! # struct Foo;
+//! impl Foo {
+//!     #[generate_interface(static)]
+//!     fn from_config(path: &str) -> Foo {
+//!         Foo
+//!     }
+//! }
+//! ```
+//!
+//! Generic methods need the concrete type flapigen should use for each type parameter
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! # struct Foo;
+//! impl Foo {
+//!     #[generate_interface(generic(T = "String"))]
+//!     fn push<T: Into<String>>(&mut self, v: T) {
+//!     }
+//! }
+//! ```
+//!
+//! A generic struct is written for the types given to `instantiate`, the class is named after
+//! the struct and these types ie `WrapperString` for `Wrapper<String>`. The type parameters of
+//! its impl blocks are replaced with them in the signatures
+//! ```
+//! # use rifgen_attr::{generate_interface, generate_interface_doc};
+//! #[generate_interface_doc(instantiate(T = "String"))]
+//! struct Wrapper<T> {
+//!     inner: T,
+//! }
+//!
+//! impl<T: Clone> Wrapper<T> {
+//!     #[generate_interface]
+//!     fn get(&self) -> T {
+//!         self.inner.clone()
+//!     }
+//! }
+//! ```
+//!
+//! Use `name` to choose the name of the method in the foreign language. It's used as is whatever
+//! the [`TypeCases`]. On a trait it renames the callback interface, methods still take it as
+//! `Box<dyn Trait>` which flapigen maps to the renamed interface
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! # struct Foo;
+//! impl Foo {
+//!     #[generate_interface(name = "toString")]
+//!     fn to_string_repr(&self) -> String {
+//!         String::new()
+//!     }
+//! }
+//! ```
+//!
+//! Use `skip_arg` to leave parameters supplied on the rust side out of the foreign signature.
+//! The glue calling the method has to provide them
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! # struct Foo;
+//! # struct Context;
+//! impl Foo {
+//!     #[generate_interface(skip_arg = "ctx")]
+//!     fn run(&mut self, ctx: &mut Context, steps: i32) {
+//!     }
+//! }
+//! ```
+//!
+//! `only` limits a method or constant of an impl block to the given languages, one of `java`,
+//! `kotlin`, `cpp` or `python`, and may be repeated. `java` also covers [`Language::Kotlin`]
+//! which uses flapigen's java backend
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! # struct Foo;
+//! impl Foo {
+//!     #[generate_interface(only = "java")]
+//!     fn attach_activity(&mut self) {
+//!     }
+//!     #[generate_interface(only = "cpp", only = "python")]
+//!     fn native_handle(&self) -> u64 {
+//!         0
+//!     }
+//! }
+//! ```
+//!
+//! Methods of `impl Trait for Foo` blocks may be annotated too. They're methods of `Foo` in
+//! the interface file, which imports the trait so flapigen can call them
+//!
+//! `ignore` leaves a single method of an annotated trait or impl block out of the interface
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! #[generate_interface]
+//! trait Listener {
+//!     fn on_event(&self, id: i32);
+//!     #[generate_interface(ignore)]
+//!     fn debug_name(&self) -> String {
+//!         String::new()
+//!     }
+//! }
+//! ```
+//!
+//! Associated constants set to integer, `bool`, `char`, `&str` or byte string literals become
+//! static getters. Byte strings, ie `const MAGIC: &[u8] = b"RIFG";`, are returned as `&[u8]`
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! # struct Foo;
+//! impl Foo {
+//!     #[generate_interface]
+//!     const MAX: i32 = 100;
+//! }
+//! ```
+//!
+//! This crate works with doc comments so all doc comments would be preserved, whether they're
+//! written as `///`, `/** */` or `#[doc = "..."]`, in the order they're written.
+//! Use `#[generate_interface_doc]` on structs to preserve the doc comment of the struct
 //! ```
 //! ///Data holder
 //! # use rifgen_attr::generate_interface_doc;
@@ -98,6 +247,24 @@
 //! }
 //! ```
 //!
+//! Plain data structs can be exposed as value classes with `#[generate_interface(value)]`. Each
+//! `pub` field of a primitive type or `String` gets a getter and setter ie `get_x` and `set_x`,
+//! cased like any other method, and a constructor taking all fields is added when those are all
+//! of them. Fields of other types are reported as unsupported, while zero sized markers such as
+//! `PhantomData<T>` are left out and set to their default by the constructor
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! # use std::marker::PhantomData;
+//! # struct Screen;
+//! #[repr(C)]
+//! #[generate_interface(value)]
+//! pub struct Point {
+//!     pub x: f64,
+//!     pub y: f64,
+//!     pub space: PhantomData<Screen>,
+//! }
+//! ```
+//!
 //! For `trait` just annotate the trait definition
 //! ```
 //! ///MyCallback documentation
@@ -109,6 +276,30 @@
 //!     }
 //! }
 //! ```
+//! The doc comment of traits and enums is kept either way. `#[generate_interface_doc]` may be
+//! used instead of `#[generate_interface]` on them to make that explicit
+//! ```
+//! # use rifgen_attr::generate_interface_doc;
+//! ///Told about every click
+//! #[generate_interface_doc]
+//! trait OnClick {
+//!     fn on_click(&self);
+//! }
+//! ```
+//!
+//! The foreign implementation of a callback may return primitive types, `String` or `Option`s
+//! of them ie `fn provide_value(&self) -> i32;`. Other return types are reported as unsupported
+//!
+//! Callbacks are passed as `Box<dyn Trait>` when the rust side keeps them, or as `&dyn Trait`
+//! and `&mut dyn Trait`, also inside an `Option`, when they're only used during the call.
+//! Borrowed callbacks are noted in the doc comment of the method
+//! Generic methods and methods bounded by `where Self: Sized` can't be called on `dyn Trait`,
+//! so they're reported as unsupported. `where` clauses elsewhere only add bounds and are ignored
+//!
+//! A callback also has the methods of its annotated supertraits, and of theirs, since the
+//! foreign implementation must provide them too. They're written with the trait they're declared
+//! in ie `notify = Observer::notify(&self);` for `trait ExtendedObserver: Observer`
+//!
 //! For `enum`, it's similar to `trait`
 //! ```
 //! # use rifgen_attr::generate_interface;
@@ -118,74 +309,4789 @@
 //!     Two
 //! }
 //! ```
+//! flapigen numbers the foreign variants in the order they're declared, so when an enum has
+//! explicit discriminants like `Warn = 10` the rust value of each variant is added to its doc
+//! comment. Only integer literals are supported as discriminants
+//!
+//! The doc comments of variants, written with `///` or `#[doc = "..."]`, are written above each
+//! foreign variant like those of methods
+//!
+//! Types are written with their name unless annotated types in different modules share it.
+//! Then each is written with its path from the crate root ie `net::Config`, and its foreign
+//! name includes the module ie `NetConfig`. Source folders are taken as the root of the crate
+//!
+//! Types re-exported under another name, ie `pub use inner::InnerFoo as Foo;`, are written with
+//! that name. When a type is re-exported under several names the first one found is used
+//! Signatures, including those of callbacks, may use types imported with `use`, also under
+//! another name ie `use crate::model::Other as Renamed;`, which are written as the type imported
+//!
+//! Annotated free functions, ie `#[generate_interface] pub fn init_library(level: i32)`, are
+//! written as static methods of the class set with [`Generator::free_fn_class`]
+//!
+//! Type aliases without generic parameters, ie `type Handle = u64;`, are replaced in signatures
+//! by the type they stand for before the type map is applied
+//!
+//! A newtype marked `#[generate_interface(transparent)]`, ie `struct Meters(f64);`, is replaced
+//! the same way by its field, so a method returning `Meters` is written as returning `f64`.
+//! flapigen then needs a `foreign_typemap!` converting between the two. Only tuple structs with
+//! a single field are supported
+//!
+//! Nested generic types such as `Option<Vec<T>>`, `Vec<Option<T>>` or `Result<Vec<T>, E>` are
+//! resolved layer by layer, so with `with_type_map("Duration", "u64")` `Option<Vec<Duration>>`
+//! is written as `Option<Vec<u64>>`. When
+//! any layer has no counterpart in the target language the whole method is unsupported
+mod cfg;
 mod enums;
+mod error;
 mod generator_lib;
 mod maps;
+pub mod model;
+mod paths;
+mod symbols;
 mod text_formatter;
 mod traits;
 mod types_structs;
+#[cfg(feature = "watch")]
+mod watch;
 
 pub extern crate rifgen_attr;
 
+use crate::cfg::CfgSet;
+pub use crate::error::{Diff, GenError, Location, Result};
 use crate::generator_lib::FileGenerator;
-use std::path::Path;
+use crate::maps::{RustType, TypeMap};
+use crate::model::{GenSummary, Interface};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 /// The various type cases to use when generating interface files
 /// i.e CamelCase or snake_case or just leave the style unchanged
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TypeCases {
     /// Various names of methods and variants are untouched.
     /// This is the default setting
     Default,
-    /// Convert all method names to CamelCase
+    /// Convert method and parameter names to lowerCamelCase and type names to PascalCase
     CamelCase,
+    /// Convert method, parameter and type names to PascalCase
+    PascalCase,
     /// Convert all method method names to snake_case
     SnakeCase,
 }
 
-/// The builder to use in build.rs file to generate the interface file
-pub struct Generator<P: AsRef<Path>> {
+/// How enum variant names are written in the interface file.
+/// It's independent of [`TypeCases`] since enum constants usually follow their own convention
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EnumCase {
+    /// Variants are written as they are in the source code.
+    /// This is the default setting
+    Unchanged,
+    /// `DarkRed` becomes `DARK_RED` ie for java constants
+    ScreamingSnake,
+    /// `DARK_RED` becomes `DarkRed`
+    Pascal,
+}
+
+/// The order the methods of a class or callback are written in.
+/// Constructors are written first either way
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Order {
+    /// Static methods come before the other methods, each sorted by name.
+    /// This is the default setting
+    Sorted,
+    /// The order they're declared in, by the path of their file then their position in it, so
+    /// it doesn't depend on the order the file system lists files
+    SourceDeclaration,
+}
+
+/// What's done with the constructors of a class after the first one found, which is the first one
+/// declared in the first file scanned
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConstructorPolicy {
+    /// They're all constructors, overloads in the foreign class, so their parameter types must
+    /// differ. This is the default setting
+    Overload,
+    /// They're written as static methods returning the class ie `fn Foo::open(path: &str) -> Foo;`
+    /// for backends that only take a single constructor
+    Static,
+    /// They're unsupported and reported with the constructor that's kept
+    Reject,
+}
+
+/// How `usize` and `isize` are written in the interface file
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PointerWidth {
+    /// As `u32` and `i32`
+    W32,
+    /// As `u64` and `i64`, whatever the width on the host generating the interface
+    W64,
+    /// As they are, flapigen then picks the foreign type. This is the default setting
+    Native,
+}
+
+/// What a level of indentation in the interface file is written as
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Indent {
+    /// A tab per level. This is the default setting
+    Tabs,
+    /// The given number of spaces per level
+    Spaces(usize),
+}
+
+/// How lines of the interface file end
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LineEnding {
+    /// `\n` whatever the platform. This is the default setting
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+/// Every setting of a [`Generator`], ie to log the configuration or to assert two builds
+/// generate the same way. They're changed through the builder methods of the generator
+/// ```
+/// # use rifgen::{Generator, Language, Settings, TypeCases};
+/// let settings = Generator::new(TypeCases::CamelCase, Language::Java, "src")
+///     .settings()
+///     .clone();
+/// assert_eq!(Generator::with_settings(settings.clone()).settings(), &settings);
+/// assert_ne!(Settings::new("src"), settings);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
     type_case: TypeCases,
-    scr_folder: P,
+    enum_case: EnumCase,
+    order: Order,
+    /// what's done with the constructors of a class after the first
+    constructors: ConstructorPolicy,
+    /// whether doc comments are converted to the markup of the target language
+    convert_docs: bool,
+    /// whether doc comments are left out
+    strip_doc_comments: bool,
+    /// comment written at the start of the interface file
+    banner: Option<String>,
+    /// only used for C++
+    cpp_namespace: Option<String>,
+    /// only used for Java and Kotlin, ie `com.example.ffi`
+    java_package: Option<String>,
+    /// the folders are walked in this order
+    scr_folders: Vec<PathBuf>,
     language: Language,
+    /// files matching any of these are skipped
+    exclude: Vec<glob::Pattern>,
+    /// the extensions of the files read in the folders
+    extensions: Vec<String>,
+    type_map: TypeMap,
+    /// the mappings only used for a language, applied after `type_map`
+    language_type_maps: Vec<(Language, TypeMap)>,
+    pointer_width: PointerWidth,
+    cfg: CfgSet,
+    /// whether generating is skipped when the manifest is unchanged
+    incremental: bool,
+    /// whether items that aren't `pub` are skipped
+    public_only: bool,
+    /// the path the rust paths of the annotated types start with
+    crate_root: Option<String>,
+    /// the only types generated, with the types they use
+    only_types: Option<Vec<String>>,
+    /// whether `impl Iterator<Item = T>` becomes `Vec<T>`
+    iterators_as_vec: bool,
+    /// whether `PathBuf` and `&Path` become `String` and `&str`
+    paths_as_strings: bool,
+    /// whether async methods are written as synchronous ones
+    async_as_blocking: bool,
+    /// whether methods whose foreign names clash get a number appended instead of being errors
+    disambiguate_overloads: bool,
+    /// prepended to the foreign name of every type
+    class_prefix: Option<String>,
+    /// the class annotated free functions are static methods of
+    free_fn_class: Option<String>,
+    indent: Indent,
+    line_ending: LineEnding,
+    /// whether files already holding what would be written are left untouched
+    overwrite_only_if_changed: bool,
+    /// whether only what's between the markers of the interface file is written
+    append: bool,
+    /// whether unsupported items are errors instead of being skipped
+    fail_on_unsupported: bool,
+    /// where the JSON list of generated symbols is written
+    symbols: Option<PathBuf>,
+    /// whether split files of types that are gone are removed
+    clean: bool,
+}
+
+/// The builder to use in build.rs file to generate the interface file
+#[derive(Clone, Debug)]
+pub struct Generator {
+    settings: Settings,
 }
 
-///Supported languages for now
+///Supported languages for now.
+/// These are the backends of flapigen, which has no swift or C backend. For iOS, generate the
+/// interface for `Cpp` and call the generated C++ classes from Objective-C++ or swift.
+/// For a C ABI, also use `Cpp`: flapigen's C++ classes wrap `extern "C"` functions it generates
+/// and converts the types of, so the interface file keeps the rust signatures
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Language {
     Java,
     Cpp,
+    /// flapigen converts `String`/`&str` to `str` and the integer types to `int` so the
+    /// generated signatures keep their rust types.
+    /// Traits become callbacks implemented by inheriting the generated python class
+    Python,
+    /// Uses flapigen's java backend since kotlin calls the generated java classes directly.
+    /// `TypeCases::Default` follows kotlin's conventions, the same as `TypeCases::CamelCase`,
+    /// and doc comments are written as KDoc.
+    /// `Option<T>` becomes a nullable `T?` once flapigen is told to annotate nullable types
+    /// ie with `JavaConfig::use_null_annotation_from_package`
+    Kotlin,
+}
+
+impl Settings {
+    /// The settings of [`Generator::builder`] for `scr_folder`
+    pub fn new<S: AsRef<Path>>(scr_folder: S) -> Settings {
+        Settings {
+            type_case: TypeCases::Default,
+            enum_case: EnumCase::Unchanged,
+            order: Order::Sorted,
+            constructors: ConstructorPolicy::Overload,
+            convert_docs: true,
+            strip_doc_comments: false,
+            banner: None,
+            cpp_namespace: None,
+            java_package: None,
+            scr_folders: vec![scr_folder.as_ref().to_path_buf()],
+            language: Language::Java,
+            exclude: vec![],
+            extensions: vec!["rs".to_string()],
+            type_map: TypeMap::default(),
+            language_type_maps: vec![],
+            pointer_width: PointerWidth::Native,
+            cfg: CfgSet::default(),
+            incremental: false,
+            public_only: false,
+            crate_root: None,
+            only_types: None,
+            iterators_as_vec: false,
+            paths_as_strings: false,
+            async_as_blocking: false,
+            disambiguate_overloads: false,
+            class_prefix: None,
+            free_fn_class: None,
+            indent: Indent::Tabs,
+            line_ending: LineEnding::Lf,
+            overwrite_only_if_changed: true,
+            append: false,
+            fail_on_unsupported: false,
+            symbols: None,
+            clean: false,
+        }
+    }
+
+    ///The built in mappings overridden by the mappings given
+    pub(crate) fn effective_type_map(&self) -> TypeMap {
+        let mut type_map = TypeMap::builtin(self.language);
+        let widths = match self.pointer_width {
+            PointerWidth::W32 => Some(("u32", "i32")),
+            PointerWidth::W64 => Some(("u64", "i64")),
+            PointerWidth::Native => None,
+        };
+        if let Some((unsigned, signed)) = widths {
+            type_map.insert(parse_mapped_type("usize"), parse_mapped_type(unsigned));
+            type_map.insert(parse_mapped_type("isize"), parse_mapped_type(signed));
+        }
+        type_map.extend(self.type_map.clone());
+        for (language, map) in &self.language_type_maps {
+            if *language == self.language {
+                type_map.extend(map.clone());
+            }
+        }
+        type_map
+    }
+
+    ///Whether the name of `file_path` ends with one of the extensions, which may have several
+    /// parts ie `rs.tmpl`
+    pub(crate) fn has_extension(&self, file_path: &Path) -> bool {
+        let name = file_path.file_name().unwrap_or_default().to_string_lossy();
+        self.extensions.iter().any(|it| {
+            name.strip_suffix(it.as_str())
+                .is_some_and(|it| it.len() > 1 && it.ends_with('.'))
+        })
+    }
+
+    ///Patterns are matched against both the full path and the path relative to the folder
+    /// it was found in, so `**/tests/**`, `*.generated.rs` and `gen/*.rs` all work
+    pub(crate) fn is_excluded(&self, starting_point: &Path, file_path: &Path) -> bool {
+        let relative = file_path.strip_prefix(starting_point).unwrap_or(file_path);
+        self.exclude
+            .iter()
+            .any(|it| it.matches_path(file_path) || it.matches_path(relative))
+    }
 }
 
-impl<S: AsRef<Path>> Generator<S> {
+impl Generator {
     /// Creates a new generator instance
     ///
     /// `scr_folder` refers to the starting folder where it is recursively walked
-    ///through to find other files
-    pub fn new(type_case: TypeCases, language: Language, scr_folder: S) -> Generator<S> {
-        Generator {
-            type_case,
-            scr_folder,
-            language,
+    ///through to find other files. It may also be a single rust file, which is then the only
+    /// file read and is taken as the crate root ie for quick experiments
+    pub fn new<S: AsRef<Path>>(
+        type_case: TypeCases,
+        language: Language,
+        scr_folder: S,
+    ) -> Generator {
+        Generator::builder(scr_folder)
+            .type_case(type_case)
+            .language(language)
+    }
+
+    /// Starts a generator for `scr_folder`, like [`Generator::new`], which may be a folder or a
+    /// single file. It uses [`TypeCases::Default`] and [`Language::Java`] until
+    /// [`Generator::type_case`] and [`Generator::language`] change them.
+    /// The generator is its own builder so every setting is chained the same way
+    /// ```no_run
+    /// # use rifgen::{Generator, Language, TypeCases};
+    /// # fn main() -> Result<(), rifgen::GenError> {
+    /// Generator::builder("src")
+    ///     .type_case(TypeCases::CamelCase)
+    ///     .language(Language::Cpp)
+    ///     .exclude("**/tests/**")
+    ///     .build()
+    ///     .generate_interface("glue.in")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder<S: AsRef<Path>>(scr_folder: S) -> Generator {
+        Generator::with_settings(Settings::new(scr_folder))
+    }
+
+    /// Continues from `settings`, ie those of another generator
+    pub fn with_settings(settings: Settings) -> Generator {
+        Generator { settings }
+    }
+
+    /// The settings generation uses so far
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// How the names of methods and types are converted
+    pub fn type_case(mut self, type_case: TypeCases) -> Generator {
+        self.settings.type_case = type_case;
+        self
+    }
+
+    /// The language the interface is generated for
+    pub fn language(mut self, language: Language) -> Generator {
+        self.settings.language = language;
+        self
+    }
+
+    /// Ends a chain started with [`Generator::builder`]. Nothing is checked or changed, the
+    /// generator is returned as it is
+    pub fn build(self) -> Generator {
+        self
+    }
+
+    /// When `true`, [`Generator::generate_interface`] records the settings and the modification
+    /// time and size of every source file in `<interface file>.manifest` and skips generating
+    /// the interface file when none of them changed since the last run.
+    /// Off by default
+    pub fn incremental(mut self, incremental: bool) -> Generator {
+        self.settings.incremental = incremental;
+        self
+    }
+
+    /// When `true`, annotated items that aren't `pub`, ie `pub(crate)` methods, are skipped.
+    /// Methods are also skipped when the struct of their impl block isn't `pub`.
+    /// Off by default
+    pub fn public_only(mut self, public_only: bool) -> Generator {
+        self.settings.public_only = public_only;
+        self
+    }
+
+    /// Writes the rust path of every annotated type from `root` ie `self_type crate::net::Config;`
+    /// with `crate_root("crate")`, so the code flapigen generates compiles wherever the interface
+    /// file is included. Without it types are written by the name they're known by, which must
+    /// be in scope there
+    pub fn crate_root(mut self, root: &str) -> Generator {
+        self.settings.crate_root = Some(root.to_string());
+        self
+    }
+
+    /// Only generates the annotated types called `names` ie `only_types(&["Foo"])`, which is
+    /// handy to look at a single class. Types in different modules that share a name are called
+    /// by their path ie `net::Config`.
+    /// The enums, callbacks and classes their signatures use are generated too so the interface
+    /// stays valid, a warning lists them
+    pub fn only_types(mut self, names: &[&str]) -> Generator {
+        self.settings.only_types = Some(names.iter().map(|it| it.to_string()).collect());
+        self
+    }
+
+    /// When `true`, `impl Iterator<Item = T>` is written as `Vec<T>` since flapigen has no lazy
+    /// iterators. The code flapigen generates then expects the method to return a `Vec<T>` so
+    /// the iterator must be collected on the rust side, ie by annotating a wrapper method
+    /// instead. Off by default: methods using iterators are unsupported
+    pub fn iterators_as_vec(mut self, iterators_as_vec: bool) -> Generator {
+        self.settings.iterators_as_vec = iterators_as_vec;
+        self
+    }
+
+    /// When `true`, `std::path::PathBuf` is written as `String` and `&Path` as `&str` so paths
+    /// are passed as text. Like [`Generator::iterators_as_vec`], the code flapigen generates
+    /// then expects the rust method to use the string types. `Cow<str>` is always written as
+    /// `String`. Off by default
+    pub fn paths_as_strings(mut self, paths_as_strings: bool) -> Generator {
+        self.settings.paths_as_strings = paths_as_strings;
+        self
+    }
+
+    /// When `true`, `async fn fetch(&self, url: &str) -> String` is written as the synchronous
+    /// `fn fetch(&self, url: &str) -> String` since flapigen can't express futures, and noted in
+    /// its doc comment. Like [`Generator::iterators_as_vec`], the code flapigen generates then
+    /// expects the output of the future so the rust glue must block on it, ie with an executor's
+    /// `block_on` in a synchronous method of the same name it's compiled against.
+    /// Off by default: async methods are unsupported
+    pub fn async_as_blocking(mut self, async_as_blocking: bool) -> Generator {
+        self.settings.async_as_blocking = async_as_blocking;
+        self
+    }
+
+    /// When `true`, a method whose name in the interface is already taken by another method of
+    /// its class, ie `set` in two impl blocks, is renamed with the first free number appended
+    /// ie `set1`. Methods keep the name they're written with in the order the files are scanned.
+    /// Off by default: the clash is unsupported and reported with where both methods are declared
+    pub fn disambiguate_overloads(mut self, disambiguate_overloads: bool) -> Generator {
+        self.settings.disambiguate_overloads = disambiguate_overloads;
+        self
+    }
+
+    /// Prepends `prefix` to the foreign name of every generated class, callback and enum ie
+    /// `class_prefix("Net")` writes `Config` as `NetConfig`, so the classes of two libraries
+    /// can't clash. Method signatures keep naming the rust types, which flapigen converts to the
+    /// prefixed classes, so a parameter of type `Config` is a `NetConfig` in the foreign code
+    pub fn class_prefix(mut self, prefix: &str) -> Generator {
+        self.settings.class_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Writes the annotated free functions as static methods of the class `name`, ie `Lib` for
+    /// `fn init_library(level: i32);` in `class Lib`, which is added if it isn't a generated class
+    /// already. Free functions are unsupported otherwise
+    pub fn free_fn_class(mut self, name: &str) -> Generator {
+        self.settings.free_fn_class = Some(name.to_string());
+        self
+    }
+
+    /// Writes `banner` as a comment at the start of the interface file ie
+    /// `header("Generated by our pipeline. Do not edit")`. Each line becomes a `//` comment since
+    /// the interface file is rust code that flapigen parses whatever the target language.
+    /// With [`Generator::generate_interface_split`] it's written to `header.in`
+    pub fn header(mut self, banner: &str) -> Generator {
+        self.settings.banner = Some(banner.to_string());
+        self
+    }
+
+    /// The C++ namespace the generated classes are declared in ie `my_project::ffi`.
+    /// flapigen reads the namespace from `CppConfig::new(output_dir, namespace)`, not from the
+    /// interface file, so it's recorded in a comment at the start of the interface file which
+    /// the build script can check against. The same name must be given to `CppConfig`.
+    /// Only used with [`Language::Cpp`]
+    pub fn cpp_namespace(mut self, namespace: &str) -> Generator {
+        self.settings.cpp_namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// The java package of the generated classes ie `com.example.ffi`.
+    /// Like [`Generator::cpp_namespace`], it's recorded in a comment at the start of the
+    /// interface file since flapigen reads it from `JavaConfig::new(output_dir, package)`.
+    /// Only used with [`Language::Java`] and [`Language::Kotlin`], generating for them returns
+    /// [`GenError::Setting`] if `package` isn't made of java identifiers separated by dots
+    pub fn java_package(mut self, package: &str) -> Generator {
+        self.settings.java_package = Some(package.to_string());
+        self
+    }
+
+    /// When `true`, only what's between the lines `// RIFGEN:BEGIN` and `// RIFGEN:END` of an
+    /// existing interface file is generated again, so hand written `foreign_typemap!` rules
+    /// around them are kept. The whole file is written when it has neither marker, and when the
+    /// markers are out of order or one is missing [`GenError::Markers`] is returned without
+    /// touching the file. [`Generator::check`] compares the file with what would be merged into
+    /// it. Off by default
+    pub fn append(mut self, append: bool) -> Generator {
+        self.settings.append = append;
+        self
+    }
+
+    /// When `true`, an interface file that already holds what would be written is left
+    /// untouched so its modification time doesn't change and build tools don't rebuild what
+    /// depends on it. On by default, use `false` to write the file on every run
+    pub fn overwrite_only_if_changed(mut self, only_if_changed: bool) -> Generator {
+        self.settings.overwrite_only_if_changed = only_if_changed;
+        self
+    }
+
+    /// When `true`, an annotated item that can't be expressed in the interface file, ie a
+    /// method returning a tuple, fails the generation with [`GenError::Unsupported`].
+    /// Off by default: the item is skipped and a warning naming it and why it isn't supported
+    /// is logged
+    pub fn fail_on_unsupported(mut self, fail_on_unsupported: bool) -> Generator {
+        self.settings.fail_on_unsupported = fail_on_unsupported;
+        self
+    }
+
+    /// Whether doc comments are converted to the markup of the target language.
+    /// For java, intra-doc links like ``[`Foo::bar`]`` become `{@link Foo#bar}`, code spans become
+    /// `<code>...</code>` and `<`, `>` and `&` are escaped. For C++, links become Doxygen's `\ref`.
+    /// Python docs are left as they are.
+    /// On by default, use `false` to keep the doc comments exactly as written
+    pub fn convert_docs(mut self, convert_docs: bool) -> Generator {
+        self.settings.convert_docs = convert_docs;
+        self
+    }
+
+    /// When `true`, no doc comments are written, neither those of the types nor those of their
+    /// methods and variants, which makes the interface file smaller.
+    /// Off by default
+    pub fn strip_doc_comments(mut self, strip_doc_comments: bool) -> Generator {
+        self.settings.strip_doc_comments = strip_doc_comments;
+        self
+    }
+
+    /// Also writes a JSON list of the generated symbols to `path` whenever the interface is
+    /// generated, ie for build tools tracking which rust files the interface depends on.
+    /// Every class, callback and enum is listed with its foreign name, the file it's defined in
+    /// and its methods or variants with theirs:
+    ///
+    /// ```json
+    /// {
+    ///   "version": 1,
+    ///   "classes": [
+    ///     {
+    ///       "name": "Foo",
+    ///       "foreign_name": "Foo",
+    ///       "file": "src/foo.rs",
+    ///       "methods": [
+    ///         {"name": "new", "foreign_name": "new", "constructor": true, "file": "src/foo.rs"}
+    ///       ]
+    ///     }
+    ///   ],
+    ///   "callbacks": [],
+    ///   "enums": []
+    /// }
+    /// ```
+    ///
+    /// `version` changes when a field is renamed or removed, not when one is added.
+    /// The JSON is written by hand so no `serde` feature is needed. [`Generator::check`] doesn't
+    /// write it
+    pub fn emit_manifest<P: AsRef<Path>>(mut self, path: P) -> Generator {
+        self.settings.symbols = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Indents the interface file with `indent`, tabs by default
+    pub fn indent(mut self, indent: Indent) -> Generator {
+        self.settings.indent = indent;
+        self
+    }
+
+    /// Ends the lines of the interface file with `line_ending`, `\n` by default whatever the
+    /// platform
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Generator {
+        self.settings.line_ending = line_ending;
+        self
+    }
+
+    /// Converts the names of enum variants to `enum_case`
+    pub fn enum_variant_case(mut self, enum_case: EnumCase) -> Generator {
+        self.settings.enum_case = enum_case;
+        self
+    }
+
+    /// The order methods are written in, [`Order::Sorted`] by default
+    pub fn order(mut self, order: Order) -> Generator {
+        self.settings.order = order;
+        self
+    }
+
+    /// What's done when a class has more than one constructor, see [`ConstructorPolicy`]
+    pub fn multiple_constructors(mut self, policy: ConstructorPolicy) -> Generator {
+        self.settings.constructors = policy;
+        self
+    }
+
+    /// Marks the cfg option `key = "value"` as active ie `with_cfg("target_os", "android")`.
+    /// Use an empty `value` for options such as `unix`.
+    ///
+    /// Once any option is set, items whose `#[cfg(...)]` doesn't hold for the active options are
+    /// skipped. `all`, `any`, `not` and `key = "value"` predicates are understood.
+    /// Without any option set, items are generated whatever their `#[cfg(...)]`
+    pub fn with_cfg(mut self, key: &str, value: &str) -> Generator {
+        self.settings.cfg.insert(key, value);
+        self
+    }
+
+    /// Writes `foreign_ty` in place of `rust_ty` wherever it appears in a signature.
+    /// Use it for types with a `foreign_typemap!` rule ie `with_type_map("Uuid", "String")`.
+    /// Later mappings for the same type override earlier ones and the built in ones.
+    ///
+    /// `std::time::Duration` is written as it is. To pass it ie as its number of milliseconds,
+    /// map it with `with_type_map("Duration", "u64")` and add a `foreign_typemap!` converting
+    /// between the two, since flapigen can't.
+    /// `std::time::SystemTime` is kept for [`Language::Java`] and [`Language::Kotlin`] since
+    /// flapigen converts it to `java.util.Date`. Other languages have no counterpart for it so
+    /// methods using it are unsupported unless it's mapped here.
+    ///
+    /// Primitive types are written as they are and flapigen converts them. For
+    /// [`Language::Java`] and [`Language::Kotlin`] it uses the smallest signed java type holding
+    /// all their values, so unsigned types take a wider one:
+    ///
+    /// | rust | java |
+    /// |------|------|
+    /// | `bool` | `boolean` |
+    /// | `i8` | `byte` |
+    /// | `u8`, `i16` | `short` |
+    /// | `u16`, `i32` | `int` |
+    /// | `u32`, `i64`, `u64`, `isize`, `usize` | `long` |
+    /// | `f32` | `float` |
+    /// | `f64` | `double` |
+    ///
+    /// For [`Language::Cpp`] they keep their width ie `u32` is `uint32_t`, `i64` is `int64_t`,
+    /// `usize` is `uintptr_t` and `isize` is `intptr_t`, while `f32` and `f64` are `float` and
+    /// `double`. Use a mapping to pass a type as another ie `with_type_map("usize", "i64")`, then
+    /// the rust method must use the mapped type too. See [`Generator::mapped_type`] and
+    /// [`Generator::pointer_width`]
+    ///
+    /// # Panics
+    /// If either of them isn't a valid rust type
+    pub fn with_type_map(mut self, rust_ty: &str, foreign_ty: &str) -> Generator {
+        self.settings
+            .type_map
+            .insert(parse_mapped_type(rust_ty), parse_mapped_type(foreign_ty));
+        self
+    }
+
+    /// Like [`Generator::with_type_map`] but only when generating for `language`, taking
+    /// precedence over the mappings for every language. A shared configuration can then map ie
+    /// `bool` to a `Bool` typedef for [`Language::Cpp`] while Java keeps `bool`.
+    /// [`Language::Kotlin`] has its own mappings, those of [`Language::Java`] don't apply to it
+    ///
+    /// # Panics
+    /// If either of them isn't a valid rust type
+    pub fn with_type_map_for(
+        mut self,
+        language: Language,
+        rust_ty: &str,
+        foreign_ty: &str,
+    ) -> Generator {
+        let maps = &mut self.settings.language_type_maps;
+        let index = match maps.iter().position(|(it, _)| *it == language) {
+            Some(index) => index,
+            None => {
+                maps.push((language, TypeMap::default()));
+                maps.len() - 1
+            }
+        };
+        maps[index]
+            .1
+            .insert(parse_mapped_type(rust_ty), parse_mapped_type(foreign_ty));
+        self
+    }
+
+    /// How `rust_ty` is written in the interface file once the built in mappings and those of
+    /// [`Generator::with_type_map`] are applied ie `String` for `Uuid` mapped to it.
+    /// `None` when the language has no counterpart for it
+    ///
+    /// # Panics
+    /// If `rust_ty` isn't a valid rust type
+    pub fn mapped_type(&self, rust_ty: &str) -> Option<String> {
+        let type_map = self.settings.effective_type_map();
+        let mut ty = parse_mapped_type(rust_ty);
+        type_map.apply(&mut ty);
+        type_map.check(&ty).ok().map(|_| ty.to_string())
+    }
+
+    /// Pins the width of `usize` and `isize` ie so a 32 bit device and a 64 bit one get the same
+    /// foreign type. They're mapped like [`Generator::with_type_map`] would, which takes
+    /// precedence, so the rust methods must use the mapped types too
+    pub fn pointer_width(mut self, pointer_width: PointerWidth) -> Generator {
+        self.settings.pointer_width = pointer_width;
+        self
+    }
+
+    /// Skips files matching the glob `pattern` ie `**/tests/**` or `*.generated.rs`.
+    /// Patterns are matched against the full path of a file and its path relative to the
+    /// source folder. Calling it again adds another pattern.
+    /// An excluded file is never read, so exclusion takes precedence over everything else
+    ///
+    /// # Panics
+    /// If `pattern` isn't a valid glob pattern
+    pub fn exclude(mut self, pattern: &str) -> Generator {
+        self.settings
+            .exclude
+            .push(glob::Pattern::new(pattern).expect("Invalid exclude pattern"));
+        self
+    }
+
+    /// The extensions of the files read while walking the source folders ie
+    /// `extensions(&["rs", "rs.tmpl"])` to also scan templates. Other files are ignored, while a
+    /// single file given as a source is always read. Defaults to `["rs"]`
+    pub fn extensions(mut self, extensions: &[&str]) -> Generator {
+        self.settings.extensions = extensions.iter().map(|it| it.to_string()).collect();
+        self
+    }
+
+    /// Adds another folder to walk through, or a single file like [`Generator::new`].
+    /// All the folders are walked, in the order they were added, before the single interface
+    /// file is written so classes may refer to types in other folders.
+    /// A type defined in more than one folder is unsupported, see
+    /// [`Generator::fail_on_unsupported`]
+    pub fn add_source_folder<S: AsRef<Path>>(mut self, scr_folder: S) -> Generator {
+        self.settings
+            .scr_folders
+            .push(scr_folder.as_ref().to_path_buf());
+        self
+    }
+
+    ///`interface_file_path` refers to the path of the output file. Any extension may be used
+    /// ie `glue.rifgen.in`.
+    /// If it exists, it would be overwritten unless it already holds the same interface,
+    /// see [`Generator::overwrite_only_if_changed`] and [`Generator::append`]
+    pub fn generate_interface<I: AsRef<Path>>(self, interface_file_path: I) -> Result<()> {
+        let interface_file_path = interface_file_path.as_ref();
+        let (language, incremental) = (self.settings.language, self.settings.incremental);
+        let append = self.settings.append;
+        let generator = self.file_generator()?;
+        let manifest_path = {
+            let mut path = interface_file_path.as_os_str().to_owned();
+            path.push(".manifest");
+            PathBuf::from(path)
+        };
+        let manifest = if incremental {
+            //computed before generating so changes made while generating are picked up next time
+            let manifest = generator.manifest(language)?;
+            let previous = std::fs::read_to_string(&manifest_path).ok();
+            if interface_file_path.exists() && previous.as_ref() == Some(&manifest) {
+                log::info!("{} is up to date", interface_file_path.display());
+                return Ok(());
+            }
+            Some(manifest)
+        } else {
+            None
+        };
+        //generated in memory first so a failure doesn't leave a half written file behind
+        let mut interface = Vec::new();
+        generator.build(language, &mut interface)?;
+        if append {
+            let existing = std::fs::read_to_string(interface_file_path).unwrap_or_default();
+            let generated = String::from_utf8_lossy(&interface).into_owned();
+            interface = merge_markers(interface_file_path, &existing, generated)?.into_bytes();
+        }
+        generator.write(interface_file_path, interface)?;
+        if let Some(manifest) = manifest {
+            std::fs::write(manifest_path, manifest)?;
+        }
+        Ok(())
+    }
+
+    /// When `true`, [`Generator::generate_interface_split`] removes the files it wrote on the
+    /// previous run for types that are gone, so flapigen doesn't keep generating dead classes.
+    /// Only files listed in the previous `index.txt` are removed, never other files in the
+    /// folder. Off by default
+    pub fn clean(mut self, clean: bool) -> Generator {
+        self.settings.clean = clean;
+        self
+    }
+
+    /// Writes the interface of each class, enum and callback to its own file in `dir` ie `Foo.in`.
+    /// What's shared by all of them is written to `header.in` and `index.txt` lists the files,
+    /// starting with `header.in`, in the order they should be concatenated.
+    /// Types whose names only differ by case get a `_2`, `_3`.. suffix so it works on case
+    /// insensitive file systems. [`Generator::incremental`] has no effect here
+    pub fn generate_interface_split<D: AsRef<Path>>(self, dir: D) -> Result<()> {
+        let language = self.settings.language;
+        self.file_generator()?.build_split(language, dir.as_ref())
+    }
+
+    /// Checks `interface_file_path` is what [`Generator::generate_interface`] would write without
+    /// touching it, ie to fail CI when the checked in interface file is out of date.
+    /// Trailing whitespace is ignored. The first differing line is returned in
+    /// [`GenError::Stale`] and a missing file is reported as [`GenError::Io`]
+    pub fn check<I: AsRef<Path>>(self, interface_file_path: I) -> Result<()> {
+        let interface_file_path = interface_file_path.as_ref();
+        let existing = std::fs::read_to_string(interface_file_path)?;
+        let append = self.settings.append;
+        let mut generated = self.generate_to_string()?;
+        if append {
+            generated = merge_markers(interface_file_path, &existing, generated)?;
+        }
+        match Diff::between(interface_file_path.to_path_buf(), &existing, &generated) {
+            Some(diff) => Err(GenError::Stale(diff)),
+            None => Ok(()),
+        }
+    }
+
+    ///The interface file generated in memory, without writing the symbol list
+    fn generate_to_string(mut self) -> Result<String> {
+        self.settings.symbols = None;
+        let language = self.settings.language;
+        let mut generated = Vec::new();
+        self.file_generator()?.build(language, &mut generated)?;
+        Ok(String::from_utf8_lossy(&generated).into_owned())
+    }
+
+    /// Writes the interface to `writer` instead of a file ie to capture it in a `Vec<u8>`.
+    /// Writes are buffered internally. [`Generator::incremental`] has no effect here
+    pub fn generate_to_writer<W: Write>(self, writer: W) -> Result<()> {
+        let language = self.settings.language;
+        self.file_generator()?.build(language, writer)
+    }
+
+    /// Scans the source folders and returns what would be written to the interface file,
+    /// ie to build other tools on the annotated types
+    pub fn collect(self) -> Result<Interface> {
+        self.file_generator()?.collect()
+    }
+
+    /// Counts what [`Generator::generate_interface`] would write without writing anything, ie
+    /// for CI dashboards. Skipped unsupported items are listed in [`GenSummary::skipped`] so with
+    /// [`Generator::fail_on_unsupported`] the first one is an error instead
+    pub fn dry_run_summary(self) -> Result<GenSummary> {
+        let language = self.settings.language;
+        self.file_generator()?.summary(language)
+    }
+
+    fn file_generator(self) -> Result<FileGenerator> {
+        if let (Language::Java | Language::Kotlin, Some(package)) =
+            (self.settings.language, &self.settings.java_package)
+        {
+            check_java_package(package)?;
+        }
+        Ok(FileGenerator::new(self.settings))
+    }
+
+    /// Generates `interface_file_path` like [`Generator::generate_interface`] then blocks,
+    /// generating it again whenever a rust file in the source folders changes.
+    /// Changes made in quick succession are handled together and a line is printed each time
+    /// the file is generated. Failures are printed and watching continues.
+    ///
+    /// Requires the `watch` feature. It's meant to be run while developing, ie from a small
+    /// binary or example, and never from build.rs. Only returns if the folders can't be watched
+    #[cfg(feature = "watch")]
+    pub fn watch<I: AsRef<Path>>(self, interface_file_path: I) -> Result<()> {
+        watch::watch(self, interface_file_path.as_ref())
+    }
+
+    /// Same as [`Generator::generate_interface`] but panics on errors.
+    /// Kept for build scripts written before `generate_interface` returned a `Result`
+    pub fn generate_interface_or_panic<I: AsRef<Path>>(self, interface_file_path: I) {
+        if let Err(err) = self.generate_interface(interface_file_path) {
+            panic!("{}", err)
+        }
+    }
+}
+
+/// The lines between which [`Generator::append`] generates
+const APPEND_BEGIN: &str = "// RIFGEN:BEGIN";
+const APPEND_END: &str = "// RIFGEN:END";
+
+///`existing` with what's between its markers replaced by `generated`, or `generated` when it
+/// has neither marker. A begin marker on the last line starts an empty region
+fn merge_markers(file: &Path, existing: &str, mut generated: String) -> Result<String> {
+    let error = |reason: &str| GenError::Markers {
+        file: file.to_path_buf(),
+        reason: reason.to_string(),
+    };
+    if !generated.is_empty() && !generated.ends_with('\n') {
+        generated.push('\n');
+    }
+    let begin = match existing.find(APPEND_BEGIN) {
+        Some(begin) => begin,
+        None if existing.contains(APPEND_END) => {
+            return Err(error("`// RIFGEN:END` has no `// RIFGEN:BEGIN` before it"))
         }
+        None => return Ok(generated),
+    };
+    if existing[..begin].contains(APPEND_END) {
+        return Err(error("`// RIFGEN:END` comes before `// RIFGEN:BEGIN`"));
+    }
+    let inside = match existing[begin..].find('\n') {
+        Some(newline) => begin + newline + 1,
+        None => return Ok(format!("{}\n{}{}\n", existing, generated, APPEND_END)),
+    };
+    let end = match existing[inside..].find(APPEND_END) {
+        Some(end) => inside + end,
+        None => return Err(error("`// RIFGEN:BEGIN` has no `// RIFGEN:END` after it")),
+    };
+    //the end marker keeps its indentation
+    let end = existing[..end]
+        .rfind('\n')
+        .map_or(0, |it| it + 1)
+        .max(inside);
+    Ok(format!(
+        "{}{}{}",
+        &existing[..inside],
+        generated,
+        &existing[end..]
+    ))
+}
+
+/// How many differing lines [`assert_interface_up_to_date`] shows
+const SHOWN_DIFFERENCES: usize = 8;
+
+/// Panics unless `interface_file_path` is what `generator` would write, like
+/// [`Generator::check`] but meant to be called from an ordinary `#[test]`. The message lists the
+/// first differing lines with their numbers so the failure is readable in CI logs
+/// ```no_run
+/// # use rifgen::{Generator, Language, TypeCases};
+/// #[test]
+/// fn interface_is_up_to_date() {
+///     rifgen::assert_interface_up_to_date(
+///         Generator::new(TypeCases::CamelCase, Language::Java, "src"),
+///         "src/java_glue.rs.in",
+///     );
+/// }
+/// ```
+///
+/// # Panics
+/// If the file is out of date or missing, or generating fails
+pub fn assert_interface_up_to_date<I: AsRef<Path>>(generator: Generator, interface_file_path: I) {
+    let path = interface_file_path.as_ref();
+    let existing = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("Unable to read {}: {}", path.display(), err));
+    let append = generator.settings.append;
+    let mut generated = generator
+        .generate_to_string()
+        .unwrap_or_else(|err| panic!("Unable to generate {}: {}", path.display(), err));
+    if append {
+        generated =
+            merge_markers(path, &existing, generated).unwrap_or_else(|err| panic!("{}", err));
+    }
+    let diffs = Diff::all_between(path.to_path_buf(), &existing, &generated);
+    if diffs.is_empty() {
+        return;
+    }
+    let mut message = format!(
+        "{} is out of date, generate it again. Differing lines:\n",
+        path.display()
+    );
+    let line = |line: &Option<String>| match line {
+        Some(line) => line.to_string(),
+        None => "<end of file>".to_string(),
+    };
+    for diff in diffs.iter().take(SHOWN_DIFFERENCES) {
+        message.push_str(&format!(
+            "{:>5} - {}\n{:>5} + {}\n",
+            diff.line,
+            line(&diff.existing),
+            "",
+            line(&diff.generated)
+        ));
+    }
+    if diffs.len() > SHOWN_DIFFERENCES {
+        message.push_str(&format!("and {} more\n", diffs.len() - SHOWN_DIFFERENCES));
     }
+    panic!("{}", message);
+}
 
-    ///`interface_file_path` refers to the path of the output file.
-    /// If it exists, it would be overwritten
-    pub fn generate_interface<I: AsRef<Path>>(self, interface_file_path: I) {
-        FileGenerator::new(self.type_case, interface_file_path, self.scr_folder)
-            .build(self.language);
+///Whether `package` is made of java identifiers separated by dots
+fn check_java_package(package: &str) -> Result<()> {
+    let is_identifier = |it: &str| {
+        let mut chars = it.chars();
+        chars
+            .next()
+            .is_some_and(|it| it.is_alphabetic() || it == '_' || it == '$')
+            && chars.all(|it| it.is_alphanumeric() || it == '_' || it == '$')
+    };
+    if package.split('.').all(is_identifier) {
+        return Ok(());
     }
+    Err(GenError::Setting {
+        setting: "java_package".to_string(),
+        reason: format!(
+            "{:?} isn't made of java identifiers separated by dots",
+            package
+        ),
+    })
+}
+
+///A type given to [`Generator::with_type_map`] or [`Generator::mapped_type`]
+fn parse_mapped_type(ty: &str) -> RustType {
+    RustType::from_syn(&syn::parse_str(ty).expect("Invalid type in type map"))
 }
 
-/*#[cfg(test)]
+#[cfg(test)]
 mod tests {
-    use crate::{Generator, Language, TypeCases};
+    use crate::{
+        ConstructorPolicy, EnumCase, GenError, Generator, Indent, Language, LineEnding, Location,
+        Order, PointerWidth, TypeCases,
+    };
+    use std::path::PathBuf;
+
+    /// Writes `files` into a fresh folder under the temp dir and returns the folder
+    pub(crate) fn fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("rifgen_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        for (file, contents) in files {
+            let path = folder.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        folder
+    }
+
+    /// Runs the generator and returns the generated interface
+    pub(crate) fn generate(generator: Generator) -> String {
+        let mut output = Vec::new();
+        generator.generate_to_writer(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
 
     #[test]
-    fn test() {
-        for i in 0..20 {
-            Generator::new(TypeCases::CamelCase, Language::Java, "C:\\Users\\taimoor\\IdeaProjects\\rifgen\\test folder")
-                .generate_interface(format!("C:\\Users\\taimoor\\IdeaProjects\\rifgen\\src\\TEST{}.in", i))
+    fn malformed_file_is_reported() {
+        let folder = fixture(
+            "malformed",
+            &[(
+                "broken.rs",
+                "struct Foo;\n\nimpl Foo {\n    fn f(&self) -> i32 { 0 } }}\n",
+            )],
+        );
+        let out = folder.with_extension("in");
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .generate_interface(&out)
+            .unwrap_err();
+        let message = err.to_string();
+        match err {
+            GenError::Parse { file, location, .. } => {
+                assert_eq!(file, folder.join("broken.rs"));
+                assert_eq!(
+                    location,
+                    Location {
+                        line: 4,
+                        column: 31
+                    }
+                );
+            }
+            _ => panic!("expected a parse error, got {}", err),
         }
+        assert!(message.contains("broken.rs:4:31"), "{}", message);
+
+        let folder = fixture(
+            "misplaced",
+            &[("lib.rs", "\n#[generate_interface]\nfn free() {}")],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("lib.rs:3:4"), "{}", err);
+    }
+
+    #[test]
+    fn python_output() {
+        let folder = fixture(
+            "python",
+            &[(
+                "lib.rs",
+                "struct Foo { data: i32 }
+                impl Foo {
+                    #[generate_interface(constructor)]
+                    fn new(val: i32) -> Foo { Foo { data: val } }
+                    #[generate_interface]
+                    fn set_listener(&mut self, cb: Box<dyn Listener>) {}
+                }
+                #[generate_interface]
+                enum Color { Red, Green }
+                #[generate_interface]
+                trait Listener { fn on_click(&self, times: i32); }",
+            )],
+        );
+        let output = generate(Generator::new(
+            TypeCases::Default,
+            Language::Python,
+            &folder,
+        ));
+        assert_eq!(
+            output,
+            "//Automatically generated by rifgen
+use crate::*;
+foreign_enum!(
+\tenum Color {
+\t\tRed = Color::Red,
+\t\tGreen = Color::Green,
+\t}
+);
+foreign_callback!(
+\tcallback Listener {
+\t\tself_type Listener;
+\t\ton_click = Listener::on_click(&self, times: i32);
+\t}
+);
+foreign_class!(
+\tclass Foo {
+\t\tself_type Foo;
+\t\tconstructor Foo::new(val: i32) -> Foo;
+\t\tfn Foo::set_listener(&mut self, cb: Box<dyn Listener>);
+\t}
+);
+"
+        );
     }
-}*/
\ No newline at end of file
+
+    #[test]
+    fn static_methods() {
+        let folder = fixture(
+            "static_methods",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface(static)]
+                    fn from_config(config_path: &str) -> Foo { Foo }
+                    #[generate_interface]
+                    fn default_foo() -> Foo { Foo }
+                }",
+            )],
+        );
+        let output = generate(Generator::new(
+            TypeCases::CamelCase,
+            Language::Java,
+            &folder,
+        ));
+        assert!(output.contains("fn Foo::from_config(configPath: &str) -> Foo; alias fromConfig;"));
+        assert!(output.contains("fn Foo::default_foo() -> Foo; alias defaultFoo;"));
+        assert!(!output.contains("constructor"));
+
+        let folder = fixture(
+            "static_receiver",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface(static)]
+                    fn get(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .fail_on_unsupported(true)
+            .generate_interface(folder.with_extension("in"))
+            .unwrap_err();
+        assert!(matches!(err, GenError::Unsupported { .. }));
+    }
+
+    #[test]
+    fn option_types() {
+        let folder = fixture(
+            "option_types",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn find(&self, id: Option<i32>) -> Option<Foo> { None }
+                    #[generate_interface]
+                    fn name(&self) -> Option<String> { None }
+                    #[generate_interface]
+                    fn set_name(&mut self, name: Option<String>, age: Option<i32>) {}
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(output.contains("fn Foo::find(&self, id: Option<i32>) -> Option<Foo>;"));
+        assert!(output.contains("fn Foo::name(&self) -> Option<String>;"));
+        assert!(
+            output.contains("fn Foo::set_name(&mut self, name: Option<String>, age: Option<i32>);")
+        );
+    }
+
+    #[test]
+    fn multiple_source_folders() {
+        let core = fixture(
+            "folders_core",
+            &[(
+                "foo.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn bar(&self) -> Bar { Bar }
+                }",
+            )],
+        );
+        let ffi = fixture(
+            "folders_ffi",
+            &[(
+                "bar.rs",
+                "impl Bar {
+                    #[generate_interface]
+                    fn value(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let output = generate(
+            Generator::new(TypeCases::Default, Language::Java, &core).add_source_folder(&ffi),
+        );
+        //Bar is placed before Foo since Foo depends on it
+        let bar = output.find("class Bar").unwrap();
+        assert!(bar < output.find("class Foo").unwrap());
+
+        let shared = fixture(
+            "folders_shared",
+            &[("foo.rs", "#[generate_interface] enum Bar { One }")],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &core)
+            .add_source_folder(&ffi)
+            .add_source_folder(&shared)
+            .fail_on_unsupported(true)
+            .generate_interface(shared.with_extension("in"))
+            .unwrap_err();
+        assert!(matches!(err, GenError::Unsupported { .. }));
+    }
+
+    #[test]
+    fn excluded_files_are_skipped() {
+        let folder = fixture(
+            "exclude",
+            &[
+                (
+                    "lib.rs",
+                    "impl Foo {
+                        #[generate_interface]
+                        fn value(&self) -> i32 { 0 }
+                    }",
+                ),
+                (
+                    "tests/fixture.rs",
+                    "impl Fixture {
+                        #[generate_interface]
+                        fn value(&self) -> i32 { 0 }
+                    }",
+                ),
+                (
+                    "types.generated.rs",
+                    "#[generate_interface] enum Generated { One }",
+                ),
+            ],
+        );
+        let output = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder)
+                .exclude("**/tests/**")
+                .exclude("*.generated.rs"),
+        );
+        assert!(output.contains("class Foo"));
+        assert!(!output.contains("Fixture"));
+        assert!(!output.contains("Generated"));
+    }
+
+    #[test]
+    fn file_and_writer_output_match() {
+        let folder = fixture(
+            "writer",
+            &[("lib.rs", "#[generate_interface] enum Color { Red }")],
+        );
+        let out = folder.with_extension("in");
+        Generator::new(TypeCases::Default, Language::Cpp, &folder)
+            .generate_interface(&out)
+            .unwrap();
+        let output = generate(Generator::new(TypeCases::Default, Language::Cpp, &folder));
+        assert!(output.starts_with("//Automatically generated by rifgen"));
+        assert_eq!(std::fs::read_to_string(out).unwrap(), output);
+    }
+
+    #[test]
+    fn custom_type_map() {
+        let folder = fixture(
+            "type_map",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn find(&self, id: uuid::Uuid) -> Option<Uuid> { None }
+                    #[generate_interface]
+                    fn created(&self) -> DateTime<Utc> { Utc::now() }
+                }",
+            )],
+        );
+        let output = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder)
+                .with_type_map("Uuid", "i64")
+                .with_type_map("Uuid", "String")
+                .with_type_map("DateTime<Utc>", "i64"),
+        );
+        assert!(output.contains("fn Foo::find(&self, id: String) -> Option<String>;"));
+        assert!(output.contains("fn Foo::created(&self) -> i64;"));
+    }
+
+    #[test]
+    fn inactive_cfg_is_skipped() {
+        let folder = fixture(
+            "cfg",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[cfg(target_os = \"android\")]
+                    #[generate_interface]
+                    fn path(&self) -> String { String::new() }
+                    #[cfg(not(target_os = \"android\"))]
+                    #[generate_interface]
+                    fn path(&self) -> &str { \"\" }
+                }",
+            )],
+        );
+        let output = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder)
+                .with_cfg("target_os", "android"),
+        );
+        assert!(output.contains("fn Foo::path(&self) -> String;"));
+        assert!(!output.contains("&str"));
+    }
+
+    #[test]
+    fn parallel_output_matches_sequential() {
+        let files = (0..40)
+            .map(|i| {
+                (
+                    format!("module_{}/lib.rs", i),
+                    format!(
+                        "#[generate_interface_doc] struct Item{i} {{}}
+                        impl Item{i} {{
+                            #[generate_interface(constructor)]
+                            fn new() -> Item{i} {{ Item{i} {{}} }}
+                            #[generate_interface]
+                            fn next(&self, other: Item{j}) -> Item{j} {{ other }}
+                        }}
+                        #[generate_interface] enum Kind{i} {{ A, B }}",
+                        i = i,
+                        j = (i + 1) % 40
+                    ),
+                )
+            })
+            .collect::<Vec<_>>();
+        let files = files
+            .iter()
+            .map(|(file, contents)| (file.as_str(), contents.as_str()))
+            .collect::<Vec<_>>();
+        let folder = fixture("parallel", &files);
+        let parallel = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        let sequential = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+            .install(|| generate(Generator::new(TypeCases::Default, Language::Java, &folder)));
+        assert!(parallel.contains("class Item39"));
+        assert_eq!(parallel, sequential);
+        assert_eq!(
+            parallel,
+            generate(Generator::new(TypeCases::Default, Language::Java, &folder))
+        );
+    }
+
+    #[test]
+    fn incremental_generation() {
+        let folder = fixture(
+            "incremental",
+            &[("lib.rs", "#[generate_interface] enum Color { Red }")],
+        );
+        let out = folder.with_extension("in");
+        let run = |type_case| {
+            Generator::new(type_case, Language::Java, &folder)
+                .incremental(true)
+                .generate_interface(&out)
+                .unwrap();
+            std::fs::read_to_string(&out).unwrap()
+        };
+        assert!(run(TypeCases::Default).contains("Red"));
+        //nothing changed so the file isn't written again
+        std::fs::write(&out, "stale").unwrap();
+        assert_eq!(run(TypeCases::Default), "stale");
+        //changing the settings invalidates it
+        assert!(run(TypeCases::CamelCase).contains("Red"));
+        std::fs::write(&out, "stale").unwrap();
+        assert_eq!(run(TypeCases::CamelCase), "stale");
+        //and so does changing a source file
+        std::fs::write(
+            folder.join("lib.rs"),
+            "#[generate_interface] enum Color { Red, Green }",
+        )
+        .unwrap();
+        assert!(run(TypeCases::CamelCase).contains("Green"));
+
+        //the symbol list and the merge are part of what's written
+        let symbols = folder.with_extension("json");
+        let generator =
+            || Generator::new(TypeCases::CamelCase, Language::Java, &folder).incremental(true);
+        generator()
+            .emit_manifest(&symbols)
+            .generate_interface(&out)
+            .unwrap();
+        assert!(symbols.exists());
+        std::fs::write(&out, "preamble\n// RIFGEN:BEGIN\n// RIFGEN:END\n").unwrap();
+        generator()
+            .emit_manifest(&symbols)
+            .append(true)
+            .generate_interface(&out)
+            .unwrap();
+        let merged = std::fs::read_to_string(&out).unwrap();
+        assert!(
+            merged.starts_with("preamble\n// RIFGEN:BEGIN\n"),
+            "{}",
+            merged
+        );
+        assert!(merged.contains("Green"), "{}", merged);
+    }
+
+    #[test]
+    fn generic_hints() {
+        let folder = fixture(
+            "generic",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface(generic(T = \"String\"))]
+                    fn push<T: Into<String>>(&mut self, v: T) {}
+                    #[generate_interface(generic(T = \"i32\"))]
+                    fn first<'a, T>(&'a self, v: Option<T>) -> Vec<T> where T: Copy { vec![] }
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(output.contains("fn Foo::push(&mut self, v: String);"));
+        assert!(output.contains("fn Foo::first(&self, v: Option<i32>) -> Vec<i32>;"));
+
+        let folder = fixture(
+            "generic_unhinted",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn push<T: Into<String>>(&mut self, v: T) {}
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Foo::push");
+                assert!(reason.contains("`T`"), "{}", reason);
+            }
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn vec_types() {
+        let folder = fixture(
+            "vec",
+            &[
+                (
+                    "lib.rs",
+                    "impl Foo {
+                        #[generate_interface]
+                        fn set_ids(&mut self, ids: Vec<i32>) {}
+                        #[generate_interface]
+                        fn ids(&self) -> Vec<i32> { vec![] }
+                        #[generate_interface]
+                        fn set_names(&mut self, names: Vec<String>) {}
+                        #[generate_interface]
+                        fn names(&self) -> Vec<String> { vec![] }
+                        #[generate_interface]
+                        fn set_children(&mut self, children: std::vec::Vec<model::Bar>) {}
+                        #[generate_interface]
+                        fn children(&self) -> Vec<Bar> { vec![] }
+                        #[generate_interface]
+                        fn siblings(&self) -> Vec<Self> { vec![] }
+                    }",
+                ),
+                ("model.rs", "#[generate_interface_doc] struct Bar {}"),
+            ],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "fn Foo::set_ids(&mut self, ids: Vec<i32>);",
+            "fn Foo::ids(&self) -> Vec<i32>;",
+            "fn Foo::set_names(&mut self, names: Vec<String>);",
+            "fn Foo::names(&self) -> Vec<String>;",
+            "fn Foo::set_children(&mut self, children: Vec<Bar>);",
+            "fn Foo::children(&self) -> Vec<Bar>;",
+            "fn Foo::siblings(&self) -> Vec<Foo>;",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+
+        for (name, method) in [
+            ("vec_ref", "fn keys(&self) -> Vec<&str> { vec![] }"),
+            ("vec_nested", "fn grid(&self) -> Vec<Vec<i32>> { vec![] }"),
+            ("vec_callback", "fn listeners(&self, l: Vec<Listener>) {}"),
+        ] {
+            let folder = fixture(
+                name,
+                &[(
+                    "lib.rs",
+                    &format!(
+                        "#[generate_interface] trait Listener {{ fn on_event(&self); }}
+                        impl Foo {{ #[generate_interface] {} }}",
+                        method
+                    ),
+                )],
+            );
+            let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+                .fail_on_unsupported(true)
+                .generate_to_writer(Vec::new())
+                .unwrap_err();
+            assert!(
+                matches!(err, GenError::Unsupported { .. }),
+                "expected an unsupported error, got {}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn renamed_methods() {
+        let folder = fixture(
+            "rename",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface(name = \"toString\")]
+                    fn to_string_repr(&self) -> String { String::new() }
+                    #[generate_interface(static, name = \"of\")]
+                    fn from_value(value_ok: i32) -> Foo { Foo }
+                }",
+            )],
+        );
+        for type_case in [TypeCases::Default, TypeCases::SnakeCase] {
+            let output = generate(Generator::new(type_case, Language::Java, &folder));
+            assert!(output.contains("fn Foo::to_string_repr(&self) -> String; alias toString;"));
+            assert!(output.contains("alias of;"));
+        }
+
+        let folder = fixture(
+            "rename_clash",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn size(&self) -> i32 { 0 }
+                    #[generate_interface(name = \"size\")]
+                    fn len(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "Foo::len"),
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn enum_variant_case() {
+        let folder = fixture(
+            "enum_case",
+            &[(
+                "lib.rs",
+                "#[generate_interface] enum Color { DarkRed, LightBlue }",
+            )],
+        );
+        let output = generate(
+            Generator::new(TypeCases::CamelCase, Language::Java, &folder)
+                .enum_variant_case(EnumCase::ScreamingSnake),
+        );
+        assert!(output.contains("DARK_RED = Color::DarkRed,"));
+        assert!(output.contains("LIGHT_BLUE = Color::LightBlue,"));
+    }
+
+    #[test]
+    fn split_output() {
+        let folder = fixture(
+            "split",
+            &[(
+                "lib.rs",
+                "#[generate_interface] enum Color { Red }
+                impl Foo {
+                    #[generate_interface]
+                    fn color(&self) -> Color { Color::Red }
+                }
+                impl FOO {
+                    #[generate_interface]
+                    fn foo(&self) -> Foo { Foo }
+                }
+                impl Index {
+                    #[generate_interface]
+                    fn get(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let dir = folder.with_extension("split");
+        let _ = std::fs::remove_dir_all(&dir);
+        Generator::new(TypeCases::Default, Language::Java, &folder)
+            .generate_interface_split(&dir)
+            .unwrap();
+        let index = std::fs::read_to_string(dir.join("index.txt")).unwrap();
+        assert_eq!(
+            index.lines().collect::<Vec<_>>(),
+            ["header.in", "Color.in", "Foo_2.in", "FOO.in", "Index.in"]
+        );
+        let concatenated = index
+            .lines()
+            .map(|it| std::fs::read_to_string(dir.join(it)).unwrap())
+            .collect::<String>();
+        assert_eq!(
+            concatenated,
+            generate(Generator::new(TypeCases::Default, Language::Java, &folder))
+        );
+    }
+
+    #[test]
+    fn boxed_callbacks() {
+        let folder = fixture(
+            "callbacks",
+            &[(
+                "lib.rs",
+                "#[generate_interface]
+                trait MyCallback { fn on_event(&self, id: i32); }
+                impl Foo {
+                    #[generate_interface]
+                    fn set_listener(&mut self, cb: Box<dyn MyCallback>) {}
+                    #[generate_interface]
+                    fn set_optional(&mut self, cb: Option<Box<dyn MyCallback + 'static>>) {}
+                    #[generate_interface]
+                    fn set_shared(&mut self, cb: Box<dyn listeners::MyCallback + Send + 'static>) {}
+                    #[generate_interface]
+                    fn set_other(&mut self, cb: Box<dyn Other + 'static>) {}
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "fn Foo::set_listener(&mut self, cb: Box<dyn MyCallback>);",
+            "fn Foo::set_optional(&mut self, cb: Option<Box<dyn MyCallback>>);",
+            "fn Foo::set_shared(&mut self, cb: Box<dyn MyCallback + Send>);",
+            //not an annotated trait so it's left alone
+            "fn Foo::set_other(&mut self, cb: Box<dyn Other + 'static>);",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+        //the callback is declared before the class using it
+        assert!(output.find("callback MyCallback") < output.find("class Foo"));
+    }
+
+    #[test]
+    fn doc_comments() {
+        let folder = fixture(
+            "docs",
+            &[(
+                "lib.rs",
+                "///Holds `Vec<u8>` data
+                #[generate_interface_doc]
+                struct Foo {}
+                impl Foo {
+                    /// Creates it.
+                    /// See [`Foo::size`]
+                    #[generate_interface(constructor)]
+                    fn new() -> Foo { Foo {} }
+                    /** Number of bytes
+                    in it */
+                    #[generate_interface]
+                    fn size(&self) -> usize { 0 }
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(output.contains("\t///Holds <code>Vec&lt;u8&gt;</code> data\n"));
+        assert!(
+            output.contains("\t\t/// Creates it.\n\t\t/// See {@link Foo#size}\n\t\tconstructor")
+        );
+        assert!(
+            output.contains("\t\t/// Number of bytes\n\t\t///                    in it \n\t\tfn")
+        );
+        let output = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder).convert_docs(false),
+        );
+        assert!(output.contains("///Holds `Vec<u8>` data"));
+        assert!(output.contains("/// See [`Foo::size`]"));
+    }
+
+    #[test]
+    fn kotlin_output() {
+        let folder = fixture(
+            "kotlin",
+            &[(
+                "lib.rs",
+                "struct Foo { data: i32 }
+                impl Foo {
+                    #[generate_interface(constructor)]
+                    fn new(initial_value: i32) -> Foo { Foo { data: initial_value } }
+                    ///Same as [`Foo::get_data`] but `None` when it's 0
+                    #[generate_interface]
+                    fn get_optional_data(&self) -> Option<i32> { None }
+                    #[generate_interface]
+                    fn get_data(&self) -> i32 { self.data }
+                }
+                #[generate_interface]
+                trait OnChange { fn on_change(&self, new_value: i32); }",
+            )],
+        );
+        let output = generate(Generator::new(
+            TypeCases::Default,
+            Language::Kotlin,
+            &folder,
+        ));
+        assert_eq!(
+            output,
+            "//Automatically generated by rifgen
+use crate::*;
+use jni_sys::*;
+foreign_class!(
+\tclass Foo {
+\t\tself_type Foo;
+\t\tconstructor Foo::new(initialValue: i32) -> Foo;
+\t\tfn Foo::get_data(&self) -> i32; alias getData;
+\t\t///Same as [Foo.get_data] but `None` when it's 0
+\t\tfn Foo::get_optional_data(&self) -> Option<i32>; alias getOptionalData;
+\t}
+);
+foreign_callback!(
+\tcallback OnChange {
+\t\tself_type OnChange;
+\t\tonChange = OnChange::on_change(&self, newValue: i32);
+\t}
+);
+"
+        );
+    }
+
+    #[test]
+    fn sorted_output() {
+        let files = [
+            "#[generate_interface_doc] struct Foo {}
+            impl Foo {
+                #[generate_interface]
+                fn size(&self) -> i32 { 0 }
+                #[generate_interface]
+                fn from_name(name: &str) -> Foo { Foo {} }
+                #[generate_interface]
+                fn clear(&mut self) {}
+            }
+            #[generate_interface] trait Listener { fn on_stop(&self); fn on_start(&self); }",
+            "impl Foo {
+                #[generate_interface(constructor)]
+                fn new() -> Foo { Foo {} }
+                #[generate_interface(static)]
+                fn default_size() -> i32 { 0 }
+            }
+            impl Bar {
+                #[generate_interface]
+                fn add(&mut self, l: Box<dyn Listener>) {}
+            }",
+        ];
+        let first = fixture("sorted_a", &[("a.rs", files[0]), ("b.rs", files[1])]);
+        let second = fixture("sorted_b", &[("a.rs", files[1]), ("b.rs", files[0])]);
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &first));
+        assert_eq!(
+            output,
+            generate(Generator::new(TypeCases::Default, Language::Java, &second))
+        );
+        let position = |it: &str| output.find(it).unwrap();
+        let order = [
+            "callback Listener",
+            "on_start",
+            "on_stop",
+            "class Bar",
+            "class Foo",
+            "constructor Foo::new",
+            "fn Foo::default_size",
+            "fn Foo::from_name",
+            "fn Foo::clear",
+            "fn Foo::size",
+        ];
+        assert!(
+            order.windows(2).all(|it| position(it[0]) < position(it[1])),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn slice_types() {
+        let folder = fixture(
+            "slice",
+            &[
+                (
+                    "lib.rs",
+                    "impl Foo {
+                        #[generate_interface]
+                        fn write(&mut self, data: &[u8]) -> usize { 0 }
+                        #[generate_interface]
+                        fn fill(&self, buffer: &mut [u8]) {}
+                        #[generate_interface]
+                        fn set_children(&mut self, children: &[model::Bar]) {}
+                        #[generate_interface]
+                        fn merge(&mut self, others: &[Self]) {}
+                    }",
+                ),
+                ("model.rs", "#[generate_interface_doc] struct Bar {}"),
+            ],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "fn Foo::write(&mut self, data: &[u8]) -> usize;",
+            "fn Foo::fill(&self, buffer: &mut [u8]);",
+            "fn Foo::set_children(&mut self, children: &[Bar]);",
+            "fn Foo::merge(&mut self, others: &[Foo]);",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+
+        for (name, method) in [
+            ("slice_mut", "fn update(&self, children: &mut [Foo]) {}"),
+            ("slice_ref", "fn keys(&self, keys: &[&str]) {}"),
+        ] {
+            let folder = fixture(
+                name,
+                &[(
+                    "lib.rs",
+                    &format!("impl Foo {{ #[generate_interface] {} }}", method),
+                )],
+            );
+            let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+                .fail_on_unsupported(true)
+                .generate_to_writer(Vec::new())
+                .unwrap_err();
+            assert!(
+                matches!(err, GenError::Unsupported { .. }),
+                "expected an unsupported error, got {}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn header_banner() {
+        let folder = fixture(
+            "banner",
+            &[("lib.rs", "#[generate_interface] enum Color { Red }")],
+        );
+        let out = folder.with_extension("rifgen.in");
+        Generator::new(TypeCases::Default, Language::Cpp, &folder)
+            .header("Generated by our pipeline\n\nDo not edit")
+            .generate_interface(&out)
+            .unwrap();
+        let output = std::fs::read_to_string(&out).unwrap();
+        assert!(output.starts_with(
+            "// Generated by our pipeline\n//\n// Do not edit\n//Automatically generated by rifgen\n"
+        ));
+        //the banner is a comment so the rest still parses
+        syn::parse_file(&output).unwrap();
+    }
+
+    #[test]
+    fn enum_discriminants() {
+        let folder = fixture(
+            "discriminants",
+            &[(
+                "lib.rs",
+                "#[generate_interface] enum Status { Ok = 0, Warn = 10, Unknown, Err = -20 }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(output.contains(
+            "\t\t///Discriminant: 0\n\t\tOk = Status::Ok,\n\
+             \t\t///Discriminant: 10\n\t\tWarn = Status::Warn,\n\
+             \t\t///Discriminant: 11\n\t\tUnknown = Status::Unknown,\n\
+             \t\t///Discriminant: -20\n\t\tErr = Status::Err,\n"
+        ));
+
+        let folder = fixture(
+            "discriminants_computed",
+            &[(
+                "lib.rs",
+                "#[generate_interface] enum Status { Ok = 0, Warn = 1 << 4 }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "Status::Warn"),
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn data_carrying_enums_are_rejected() {
+        for (name, variant, kind) in [
+            ("enum_tuple", "Click(i32)", "tuple"),
+            ("enum_struct", "Key { code: u32 }", "struct"),
+        ] {
+            let folder = fixture(
+                name,
+                &[(
+                    "lib.rs",
+                    &format!("#[generate_interface] enum Event {{ Start, {} }}", variant),
+                )],
+            );
+            let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+                .fail_on_unsupported(true)
+                .generate_to_writer(Vec::new())
+                .unwrap_err();
+            match err {
+                GenError::Unsupported { item, reason, .. } => {
+                    assert_eq!(
+                        item,
+                        format!("Event::{}", &variant[..variant.find(['(', ' ']).unwrap()])
+                    );
+                    assert!(reason.starts_with(kind), "{}", reason);
+                    assert!(reason.contains("C-like"), "{}", reason);
+                }
+                _ => panic!("expected an unsupported error, got {}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn self_types() {
+        let folder = fixture(
+            "self_types",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface(constructor)]
+                    fn new() -> Self { Foo }
+                    #[generate_interface]
+                    fn with_timeout(mut self, t: i32) -> Self { self }
+                    #[generate_interface]
+                    fn parent(&self) -> &Self { self }
+                    #[generate_interface]
+                    fn merge(&mut self, other: &mut Self) -> Option<Self> { None }
+                }
+                impl<T> Wrapper<T> {
+                    #[generate_interface(constructor)]
+                    fn new() -> Self { todo!() }
+                }
+                impl model::Holder {
+                    #[generate_interface]
+                    fn copy(&self) -> Self { todo!() }
+                }
+                #[generate_interface]
+                trait Listener { fn on_copy(&self, other: &Self); }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "constructor Foo::new() -> Foo;",
+            "fn Foo::with_timeout(self, t: i32) -> Foo;",
+            "fn Foo::parent(&self) -> &Foo;",
+            "fn Foo::merge(&mut self, other: &mut Foo) -> Option<Foo>;",
+            "constructor Wrapper::new() -> Wrapper;",
+            "fn Holder::copy(&self) -> Holder;",
+            "on_copy = Listener::on_copy(&self, other: &Listener);",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+        assert!(!output.contains("Self"));
+    }
+
+    #[test]
+    fn array_types() {
+        let folder = fixture(
+            "array_types",
+            &[(
+                "lib.rs",
+                "impl Hasher {
+                    #[generate_interface]
+                    fn fill(&mut self, buf: &mut [u8; 32]) {}
+                    ///Digest of the data
+                    #[generate_interface]
+                    fn hash(&self) -> [u8; 32] { [0; 32] }
+                    #[generate_interface]
+                    fn keys(&self, keys: &[i64; 4]) -> Option<[f32; 2]> { None }
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for line in [
+            "///<code>buf</code> must hold 32 elements",
+            "fn Hasher::fill(&mut self, buf: &mut [u8]);",
+            "///Digest of the data\n\t\t///Returns 32 elements",
+            "fn Hasher::hash(&self) -> Vec<u8>;",
+            "fn Hasher::keys(&self, keys: &[i64]) -> Option<Vec<f32>>;",
+        ] {
+            assert!(output.contains(line), "{} not in {}", line, output);
+        }
+
+        let nested = fixture(
+            "nested_arrays",
+            &[(
+                "lib.rs",
+                "impl Grid {
+                    #[generate_interface]
+                    fn rows(&self) -> Vec<[u8; 4]> { vec![] }
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &nested)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        assert!(matches!(err, GenError::Unsupported { .. }));
+    }
+
+    #[test]
+    fn callback_return_values() {
+        let folder = fixture(
+            "callback_returns",
+            &[(
+                "lib.rs",
+                "#[generate_interface]
+                trait Provider {
+                    fn provide_value(&self) -> i32;
+                    fn provide_name(&self) -> String;
+                    fn lookup(&self, key: &str) -> Option<f64>;
+                    fn on_done(&self);
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "provide_value = Provider::provide_value(&self) -> i32;",
+            "provide_name = Provider::provide_name(&self) -> String;",
+            "lookup = Provider::lookup(&self, key: &str) -> Option<f64>;",
+            "on_done = Provider::on_done(&self);",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+
+        let borrowed = fixture(
+            "callback_borrowed_return",
+            &[(
+                "lib.rs",
+                "#[generate_interface]
+                trait Provider { fn name(&self) -> &str; }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &borrowed)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "Provider::name"),
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn cpp_namespace() {
+        let folder = fixture(
+            "cpp_namespace",
+            &[("lib.rs", "#[generate_interface] enum Color { Red }")],
+        );
+        let generator = |language| {
+            generate(
+                Generator::new(TypeCases::Default, language, &folder).cpp_namespace("acme::ffi"),
+            )
+        };
+        let output = generator(Language::Cpp);
+        assert!(output.starts_with(
+            "//C++ namespace: acme::ffi. Use CppConfig::new(output_dir, \"acme::ffi\".into())\n"
+        ));
+        syn::parse_file(&output).unwrap();
+        assert!(!generator(Language::Java).contains("namespace"));
+    }
+
+    #[test]
+    fn stale_interface_file() {
+        let folder = fixture(
+            "check",
+            &[("lib.rs", "#[generate_interface] enum Color { Red, Green }")],
+        );
+        let out = folder.with_extension("in");
+        let generator = || Generator::new(TypeCases::Default, Language::Java, &folder);
+        generator().generate_interface(&out).unwrap();
+        generator().check(&out).unwrap();
+
+        let output = std::fs::read_to_string(&out).unwrap();
+        std::fs::write(&out, output.replace('\n', "  \r\n") + "\n\n").unwrap();
+        generator().check(&out).unwrap();
+
+        std::fs::write(&out, output.replace("Green = Color::Green,\n", "")).unwrap();
+        match generator().check(&out).unwrap_err() {
+            GenError::Stale(diff) => {
+                assert_eq!(diff.file, out);
+                assert_ne!(diff.existing, diff.generated);
+                assert_eq!(diff.generated.as_deref(), Some("\t\tGreen = Color::Green,"));
+                assert_eq!(
+                    output.lines().nth(diff.line - 1),
+                    Some("\t\tGreen = Color::Green,")
+                );
+            }
+            err => panic!("expected a stale error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn associated_constants() {
+        let folder = fixture(
+            "constants",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    ///Largest size
+                    #[generate_interface]
+                    const MAX: i32 = 100;
+                    #[generate_interface]
+                    const MIN: i64 = -1;
+                    #[generate_interface(name = \"isEnabled\")]
+                    const ENABLED: bool = true;
+                    #[generate_interface]
+                    const NAME: &'static str = \"foo\";
+                    #[generate_interface]
+                    const NEWLINE: char = '\\n';
+                    #[generate_interface]
+                    const MAGIC: &[u8] = b\"RIFG\";
+                    #[generate_interface]
+                    const HEADER: &'static [u8; 2] = b\"RF\";
+                    const HIDDEN: u8 = 0;
+                    #[generate_interface]
+                    fn get(&self) -> i32 { Self::MAX }
+                }",
+            )],
+        );
+        let output = generate(Generator::new(
+            TypeCases::CamelCase,
+            Language::Java,
+            &folder,
+        ));
+        for line in [
+            "///Largest size\n\t\tfn MAX() -> i32 { Foo::MAX }\n",
+            "fn MIN() -> i64 { Foo::MIN }\n",
+            "fn isEnabled() -> bool { Foo::ENABLED }\n",
+            "fn NAME() -> &str { Foo::NAME }\n",
+            "fn NEWLINE() -> char { Foo::NEWLINE }\n",
+            "fn MAGIC() -> &[u8] { Foo::MAGIC }\n",
+            "fn HEADER() -> &[u8] { Foo::HEADER }\n",
+            "fn Foo::get(&self) -> i32; alias get;",
+        ] {
+            assert!(output.contains(line), "{} not in {}", line, output);
+        }
+        assert!(!output.contains("HIDDEN"));
+
+        let float = fixture(
+            "float_constant",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    const RATIO: f64 = 0.5;
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &float)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "Foo::RATIO"),
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+
+        let computed = fixture(
+            "computed_constant",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    const SIZE: usize = 4 * 1024;
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &computed)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Foo::SIZE");
+                assert!(
+                    reason.contains("`SIZE` must be set to a literal"),
+                    "{}",
+                    reason
+                );
+            }
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn types_in_modules() {
+        let folder = fixture(
+            "modules",
+            &[
+                (
+                    "lib.rs",
+                    "mod net {
+                        pub struct Config;
+                        impl Config {
+                            #[generate_interface(constructor)]
+                            fn new() -> Config { Config }
+                            #[generate_interface]
+                            fn port(&self) -> u16 { 0 }
+                        }
+                    }",
+                ),
+                (
+                    "ui.rs",
+                    "pub struct Config;
+                    impl Config {
+                        #[generate_interface]
+                        fn merge(&mut self, other: &Self) {}
+                    }",
+                ),
+                (
+                    "app.rs",
+                    "use crate::model::Foo;
+                    impl Foo {
+                        #[generate_interface]
+                        fn net(&self) -> crate::net::Config { todo!() }
+                        #[generate_interface]
+                        fn ui(&self) -> Vec<super::ui::Config> { todo!() }
+                    }",
+                ),
+                ("model.rs", "pub struct Foo;"),
+            ],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for line in [
+            "class NetConfig {",
+            "self_type net::Config;",
+            "constructor net::Config::new() -> net::Config;",
+            "fn net::Config::port(&self) -> u16;",
+            "class UiConfig {",
+            "fn ui::Config::merge(&mut self, other: &ui::Config);",
+            "class Foo {",
+            "fn Foo::net(&self) -> net::Config;",
+            "fn Foo::ui(&self) -> Vec<ui::Config>;",
+        ] {
+            assert!(output.contains(line), "{} not in {}", line, output);
+        }
+        //flapigen needs a class before the classes using it
+        assert!(output.find("class UiConfig").unwrap() < output.find("class Foo").unwrap());
+
+        let ambiguous = fixture(
+            "ambiguous_type",
+            &[
+                ("lib.rs", "mod a { pub struct Config; impl Config { #[generate_interface] fn a(&self) {} } }"),
+                ("b.rs", "pub struct Config; impl Config { #[generate_interface] fn b(&self) {} }"),
+                ("c.rs", "impl Foo { #[generate_interface] fn get(&self) -> Config { todo!() } }"),
+            ],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &ambiguous)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "Foo::get"),
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn public_only() {
+        let folder = fixture(
+            "public_only",
+            &[(
+                "lib.rs",
+                "pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    pub fn shown(&self) {}
+                    #[generate_interface]
+                    pub(crate) fn helper(&self) {}
+                    #[generate_interface]
+                    fn private(&self) {}
+                }
+                pub(crate) struct Internal;
+                impl Internal {
+                    #[generate_interface]
+                    pub fn get(&self) {}
+                }
+                #[generate_interface]
+                pub enum Shown { One }
+                #[generate_interface]
+                enum Hidden { One }",
+            )],
+        );
+        let output =
+            generate(Generator::new(TypeCases::Default, Language::Java, &folder).public_only(true));
+        assert!(output.contains("fn Foo::shown(&self);"), "{}", output);
+        assert!(output.contains("enum Shown"), "{}", output);
+        for hidden in ["helper", "private", "Internal", "Hidden"] {
+            assert!(!output.contains(hidden), "{} in {}", hidden, output);
+        }
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(output.contains("fn Foo::helper(&self);") && output.contains("Internal"));
+    }
+
+    #[test]
+    fn map_types() {
+        let folder = fixture(
+            "map_types",
+            &[(
+                "lib.rs",
+                "impl Config {
+                    #[generate_interface]
+                    fn values(&self) -> HashMap<String, i32> { todo!() }
+                    #[generate_interface]
+                    fn set(&mut self, v: std::collections::BTreeMap<u32, String>) {}
+                    #[generate_interface]
+                    fn find(&self, v: &HashMap<String, bool>) -> Option<BTreeMap<i64, f64>> { None }
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "fn Config::values(&self) -> HashMap<String, i32>;",
+            "fn Config::set(&mut self, v: BTreeMap<u32, String>);",
+            "fn Config::find(&self, v: &HashMap<String, bool>) -> Option<BTreeMap<i64, f64>>;",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+
+        let nested = fixture(
+            "nested_map",
+            &[(
+                "lib.rs",
+                "impl Config {
+                    #[generate_interface]
+                    fn groups(&self) -> HashMap<String, Vec<u8>> { todo!() }
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &nested)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Config::groups");
+                assert!(reason.contains("value type `Vec<u8>`"), "{}", reason);
+            }
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn skipped_args() {
+        let folder = fixture(
+            "skip_args",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface(skip_arg = \"ctx\")]
+                    fn run(&mut self, ctx: &mut Context, steps: i32) {}
+                    #[generate_interface(skip_arg = \"ctx\", skip_arg = \"log\")]
+                    fn mix(&self, a: i32, ctx: &Context, b: i32, log: Logger) {}
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(
+            output.contains("fn Foo::run(&mut self, steps: i32);"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("fn Foo::mix(&self, a: i32, b: i32);"),
+            "{}",
+            output
+        );
+
+        let missing = fixture(
+            "missing_skip_arg",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface(skip_arg = \"ctx\")]
+                    fn run(&mut self, steps: i32) {}
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &missing)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Foo::run");
+                assert!(reason.contains("`ctx`"), "{}", reason);
+            }
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn smart_pointer_receivers() {
+        let folder = fixture(
+            "smart_pointers",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn describe(self: Arc<Self>) -> String { todo!() }
+                    #[generate_interface]
+                    fn count(self: Rc<Self>) -> i32 { 0 }
+                    #[generate_interface]
+                    fn consume(self: Box<Self>) {}
+                    #[generate_interface]
+                    fn peek(self: &Self) {}
+                    #[generate_interface]
+                    fn link(&self, other: std::sync::Arc<model::Foo>, me: Arc<Self>) {}
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "fn Foo::describe(self: Arc<Self>) -> String;",
+            "fn Foo::count(self: Rc<Self>) -> i32;",
+            "fn Foo::consume(self: Box<Self>);",
+            "fn Foo::peek(&self);",
+            "fn Foo::link(&self, other: Arc<Foo>, me: Arc<Foo>);",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+    }
+
+    #[test]
+    fn java_package() {
+        let folder = fixture(
+            "java_package",
+            &[(
+                "lib.rs",
+                "#[generate_interface] enum Color { Red }
+                impl Foo { #[generate_interface] fn get(&self) {} }",
+            )],
+        );
+        let generator = |language| {
+            Generator::new(TypeCases::Default, language, &folder).java_package("com.example.ffi")
+        };
+        let directive =
+            "//Java package: com.example.ffi. Use JavaConfig::new(output_dir, \"com.example.ffi\".into())\n";
+        for language in [Language::Java, Language::Kotlin] {
+            let output = generate(generator(language));
+            assert!(output.starts_with(directive), "{}", output);
+            assert_eq!(output.matches("Java package").count(), 1);
+        }
+        assert!(!generate(generator(Language::Cpp)).contains("package"));
+
+        let dir = folder.with_extension("split");
+        generator(Language::Java)
+            .generate_interface_split(&dir)
+            .unwrap();
+        let header = std::fs::read_to_string(dir.join("header.in")).unwrap();
+        assert!(header.starts_with(directive));
+        let class = std::fs::read_to_string(dir.join("Foo.in")).unwrap();
+        assert!(!class.contains("package"));
+    }
+
+    #[test]
+    fn invalid_java_package() {
+        let folder = fixture(
+            "invalid_java_package",
+            &[("lib.rs", "#[generate_interface] enum Color { Red }")],
+        );
+        let generator = |language| {
+            Generator::new(TypeCases::Default, language, &folder).java_package("com..example")
+        };
+        let out = folder.with_extension("in");
+        match generator(Language::Java)
+            .generate_interface(&out)
+            .unwrap_err()
+        {
+            GenError::Setting { setting, reason } => {
+                assert_eq!(setting, "java_package");
+                assert!(reason.contains("\"com..example\""), "{}", reason);
+            }
+            err => panic!("{}", err),
+        }
+        assert!(!out.exists());
+        assert!(matches!(
+            generator(Language::Kotlin).collect(),
+            Err(GenError::Setting { .. })
+        ));
+        //the package isn't used for other languages
+        assert!(generate(generator(Language::Cpp)).contains("Red"));
+    }
+
+    #[test]
+    fn type_aliases() {
+        let folder = fixture(
+            "type_aliases",
+            &[
+                (
+                    "lib.rs",
+                    "impl Foo {
+                        #[generate_interface]
+                        fn get(&self, id: Handle) -> Handle { id }
+                        #[generate_interface]
+                        fn all(&self) -> Vec<Id> { vec![] }
+                        #[generate_interface]
+                        fn listen(&mut self, cb: Callback) {}
+                        #[generate_interface]
+                        fn load(&self) -> FooResult { todo!() }
+                    }
+                    #[generate_interface]
+                    trait MyTrait { fn on_event(&self); }",
+                ),
+                (
+                    "types.rs",
+                    "pub type Handle = u64;
+                    pub(crate) type Id = Handle;
+                    pub type FooResult = Result<Handle, MyError>;
+                    mod callbacks {
+                        pub type Callback = Box<dyn crate::MyTrait>;
+                    }",
+                ),
+            ],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "fn Foo::get(&self, id: u64) -> u64;",
+            "fn Foo::all(&self) -> Vec<u64>;",
+            "fn Foo::listen(&mut self, cb: Box<dyn MyTrait>);",
+            "fn Foo::load(&self) -> Result<u64, String>;",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+
+        let conflicting = fixture(
+            "conflicting_aliases",
+            &[
+                ("a.rs", "type Handle = u64;"),
+                (
+                    "b.rs",
+                    "type Handle = i32;
+                    impl Foo { #[generate_interface] fn get(&self) -> Handle { 0 } }",
+                ),
+            ],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &conflicting)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        assert!(matches!(err, GenError::Unsupported { .. }));
+    }
+
+    #[test]
+    fn collected_interface() {
+        let folder = fixture(
+            "collected_interface",
+            &[(
+                "lib.rs",
+                "/// A foo
+                #[generate_interface_doc]
+                struct Foo;
+                impl Foo {
+                    #[generate_interface(constructor)]
+                    fn new(id: Handle) -> Foo { Foo }
+                    #[generate_interface]
+                    fn colors(&self) -> Vec<Color> { vec![] }
+                }
+                type Handle = u64;
+                #[generate_interface]
+                enum Color { Red, Green }
+                #[generate_interface]
+                trait Listener { fn on_color(&self, color: Color); }",
+            )],
+        );
+        let interface = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .collect()
+            .unwrap();
+        let foo = &interface.classes[0];
+        assert_eq!(interface.classes.len(), 1);
+        assert_eq!(foo.name, "Foo");
+        assert_eq!(foo.docs, vec![" A foo"]);
+        let new = foo.extras.iter().find(|it| it.is_constructor).unwrap();
+        let args = new.method_info.as_ref().unwrap().args();
+        assert_eq!(args.len(), 1);
+        assert_eq!(
+            (args[0].0.as_str(), args[0].1.to_string()),
+            ("id", "u64".into())
+        );
+        let colors = foo.extras.iter().find(|it| !it.is_constructor).unwrap();
+        let colors = colors.method_info.as_ref().unwrap();
+        assert_eq!(colors.receiver(), Some("&self"));
+        assert_eq!(colors.output().unwrap().to_string(), "Vec<Color>");
+        let variants = interface.enums[0].extras.iter().map(|it| &it.signature);
+        assert_eq!(variants.collect::<Vec<_>>(), vec!["Red", "Green"]);
+        assert_eq!(interface.traits[0].name, "Listener");
+    }
+
+    #[test]
+    fn string_types() {
+        let folder = fixture(
+            "string_types",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn log(&self, msg: &str) {}
+                    #[generate_interface]
+                    fn take(self, s: String) {}
+                    #[generate_interface]
+                    fn maybe(&self, tag: Option<&str>) -> Option<String> { None }
+                    #[generate_interface]
+                    fn name(&self) -> &str { \"\" }
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "fn Foo::log(&self, msg: &str);",
+            "fn Foo::take(self, s: String);",
+            "fn Foo::maybe(&self, tag: Option<&str>) -> Option<String>;",
+            "fn Foo::name(&self) -> &str;",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+    }
+
+    #[test]
+    fn ignored_methods() {
+        let folder = fixture(
+            "ignored_methods",
+            &[(
+                "lib.rs",
+                "#[generate_interface]
+                trait Listener {
+                    fn on_start(&self);
+                    #[generate_interface(ignore)]
+                    fn debug_name(&self) -> String { String::new() }
+                    fn on_stop(&self, code: i32);
+                }
+                impl Foo {
+                    #[generate_interface]
+                    fn run(&self) {}
+                    #[generate_interface(ignore)]
+                    fn internal(&self) {}
+                }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(output.contains("Listener::on_start(&self);"), "{}", output);
+        assert!(
+            output.contains("Listener::on_stop(&self, code: i32);"),
+            "{}",
+            output
+        );
+        assert!(output.contains("fn Foo::run(&self);"), "{}", output);
+        assert!(!output.contains("debug_name"), "{}", output);
+        assert!(!output.contains("internal"), "{}", output);
+    }
+
+    #[test]
+    fn tuple_types() {
+        for (name, method) in [
+            (
+                "tuple_return",
+                "fn bounds(&self) -> (f64, f64) { (0.0, 0.0) }",
+            ),
+            ("tuple_param", "fn move_to(&mut self, to: (i32, i32)) {}"),
+            (
+                "tuple_nested",
+                "fn pairs(&self) -> Vec<(i32, String)> { vec![] }",
+            ),
+        ] {
+            let folder = fixture(
+                name,
+                &[(
+                    "lib.rs",
+                    &format!("impl Foo {{ #[generate_interface] {} }}", method),
+                )],
+            );
+            let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+                .fail_on_unsupported(true)
+                .generate_to_writer(Vec::new())
+                .unwrap_err();
+            match err {
+                GenError::Unsupported { item, reason, .. } => {
+                    assert!(item.starts_with("Foo::"), "{}", item);
+                    assert!(reason.contains("tuples"), "{}", reason);
+                }
+                err => panic!("{}", err),
+            }
+        }
+
+        //`()` is nothing to return and mapped tuples are fine
+        let folder = fixture(
+            "tuple_mapped",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn reset(&mut self) -> () {}
+                    #[generate_interface]
+                    fn bounds(&self) -> (f64, f64) { (0.0, 0.0) }
+                }",
+            )],
+        );
+        let output = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder)
+                .with_type_map("(f64, f64)", "Bounds"),
+        );
+        assert!(
+            output.contains("fn Foo::bounds(&self) -> Bounds;"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn split_impl_blocks() {
+        let folder = fixture(
+            "split_impl_blocks",
+            &[
+                (
+                    "foo_core.rs",
+                    "struct Foo;
+                    impl Foo {
+                        #[generate_interface(constructor)]
+                        fn new() -> Foo { Foo }
+                        #[generate_interface]
+                        fn id(&self) -> i32 { 0 }
+                    }",
+                ),
+                (
+                    "foo_io.rs",
+                    "impl Foo {
+                        #[generate_interface(constructor)]
+                        fn open(path: String) -> Foo { Foo }
+                        #[generate_interface]
+                        fn save(&self, path: String) {}
+                    }",
+                ),
+                (
+                    "ui/foo_ui.rs",
+                    "impl crate::Foo {
+                        #[generate_interface]
+                        fn show(&self) {}
+                    }",
+                ),
+            ],
+        );
+        //nested folders don't scan `ui/foo_ui.rs` twice
+        let output = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder)
+                .add_source_folder(folder.join("ui")),
+        );
+        assert_eq!(output.matches("class Foo").count(), 1, "{}", output);
+        for method in [
+            "constructor Foo::new() -> Foo;",
+            "constructor Foo::open(path: String) -> Foo;",
+            "fn Foo::id(&self) -> i32;",
+            "fn Foo::save(&self, path: String);",
+            "fn Foo::show(&self);",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+
+        let conflicting = fixture(
+            "conflicting_constructors",
+            &[
+                (
+                    "a.rs",
+                    "struct Foo;
+                    impl Foo { #[generate_interface(constructor)] fn open(path: String) -> Foo { Foo } }",
+                ),
+                (
+                    "b.rs",
+                    "impl Foo { #[generate_interface(constructor)] fn named(name: String) -> Foo { Foo } }",
+                ),
+            ],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &conflicting)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Foo::named");
+                assert!(reason.contains("`open`"), "{}", reason);
+            }
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn borrowed_callbacks() {
+        let folder = fixture(
+            "borrowed_callbacks",
+            &[
+                (
+                    "lib.rs",
+                    "impl Foo {
+                        #[generate_interface]
+                        fn visit(&self, cb: &dyn callbacks::MyCallback) {}
+                        #[generate_interface]
+                        fn visit_mut(&self, cb: &mut (dyn callbacks::MyCallback + 'static)) {}
+                        #[generate_interface]
+                        fn maybe_visit(&self, cb: Option<&dyn callbacks::MyCallback>) {}
+                        #[generate_interface]
+                        fn keep(&mut self, cb: Box<dyn callbacks::MyCallback>) {}
+                    }",
+                ),
+                (
+                    "callbacks.rs",
+                    "#[generate_interface]
+                    pub trait MyCallback { fn call(&self, value: i32); }",
+                ),
+            ],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for method in [
+            "fn Foo::visit(&self, cb: &dyn MyCallback);",
+            "fn Foo::visit_mut(&self, cb: &mut dyn MyCallback);",
+            "fn Foo::maybe_visit(&self, cb: Option<&dyn MyCallback>);",
+            "fn Foo::keep(&mut self, cb: Box<dyn MyCallback>);",
+        ] {
+            assert!(output.contains(method), "{} not in {}", method, output);
+        }
+        let borrowed = "///<code>cb</code> is only borrowed for the duration of the call";
+        assert_eq!(output.matches(borrowed).count(), 3, "{}", output);
+    }
+
+    #[test]
+    fn indentation_and_line_endings() {
+        let folder = fixture(
+            "indentation",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    fn id(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let class = |indent: Indent, line_ending: LineEnding| {
+            let output = generate(
+                Generator::new(TypeCases::Default, Language::Java, &folder)
+                    .indent(indent)
+                    .line_ending(line_ending),
+            );
+            let start = output.find("foreign_class!").unwrap();
+            output[start..].to_string()
+        };
+        assert_eq!(
+            class(Indent::Tabs, LineEnding::Lf),
+            "foreign_class!(\n\tclass Foo {\n\t\tfn Foo::id(&self) -> i32;\n\t}\n);\n"
+        );
+        assert_eq!(
+            class(Indent::Spaces(2), LineEnding::Lf),
+            "foreign_class!(\n  class Foo {\n    fn Foo::id(&self) -> i32;\n  }\n);\n"
+        );
+        assert_eq!(
+            class(Indent::Tabs, LineEnding::CrLf),
+            "foreign_class!(\r\n\tclass Foo {\r\n\t\tfn Foo::id(&self) -> i32;\r\n\t}\r\n);\r\n"
+        );
+        assert_eq!(
+            class(Indent::Spaces(4), LineEnding::CrLf),
+            "foreign_class!(\r\n    class Foo {\r\n        fn Foo::id(&self) -> i32;\r\n    }\r\n);\r\n"
+        );
+        let output = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder)
+                .line_ending(LineEnding::CrLf),
+        );
+        assert_eq!(output.matches('\n').count(), output.matches("\r\n").count());
+    }
+
+    #[test]
+    fn enum_and_trait_docs() {
+        let folder = fixture(
+            "enum_and_trait_docs",
+            &[(
+                "lib.rs",
+                "/// The color of a tile
+                #[generate_interface_doc]
+                enum Color { Red, Green }
+                /// Told about every click
+                #[generate_interface_doc]
+                trait OnClick { fn on_click(&self); }
+                /// Kept with the usual attribute too
+                #[generate_interface]
+                enum Shape { Square }",
+            )],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for expected in [
+            "/// The color of a tile\n\tenum Color {",
+            "/// Told about every click\n\tcallback OnClick {",
+            "/// Kept with the usual attribute too\n\tenum Shape {",
+        ] {
+            assert!(output.contains(expected), "{} not in {}", expected, output);
+        }
+    }
+
+    #[test]
+    fn trait_impl_methods() {
+        let folder = fixture(
+            "trait_impl_methods",
+            &[
+                (
+                    "lib.rs",
+                    "pub struct Foo;
+                    impl Foo {
+                        #[generate_interface]
+                        pub fn id(&self) -> i32 { 0 }
+                    }
+                    impl shapes::Drawable for Foo {
+                        #[generate_interface]
+                        fn draw(&self, scale: f64) {}
+                        fn hidden(&self) {}
+                    }",
+                ),
+                (
+                    "shapes.rs",
+                    "pub trait Drawable { fn draw(&self, scale: f64); fn hidden(&self); }",
+                ),
+            ],
+        );
+        //methods of trait impls are as public as the trait
+        let output =
+            generate(Generator::new(TypeCases::Default, Language::Java, &folder).public_only(true));
+        assert_eq!(output.matches("class Foo").count(), 1, "{}", output);
+        assert!(
+            output.contains("fn Foo::draw(&self, scale: f64);"),
+            "{}",
+            output
+        );
+        assert!(output.contains("fn Foo::id(&self) -> i32;"), "{}", output);
+        assert!(!output.contains("hidden"), "{}", output);
+        assert!(
+            output.contains("use crate::shapes::Drawable;\n"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn unchanged_interface_isnt_written() {
+        let folder = fixture(
+            "unchanged_interface",
+            &[(
+                "lib.rs",
+                "impl Foo { #[generate_interface] fn id(&self) -> i32 { 0 } }",
+            )],
+        );
+        let out = folder.with_extension("in");
+        let generator = Generator::new(TypeCases::Default, Language::Java, &folder);
+        let modified = || std::fs::metadata(&out).unwrap().modified().unwrap();
+        let long_ago = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        generator.clone().generate_interface(&out).unwrap();
+        let file = std::fs::File::options().write(true).open(&out).unwrap();
+        file.set_modified(long_ago).unwrap();
+        generator.clone().generate_interface(&out).unwrap();
+        assert_eq!(modified(), long_ago);
+
+        generator
+            .overwrite_only_if_changed(false)
+            .generate_interface(&out)
+            .unwrap();
+        assert_ne!(modified(), long_ago);
+    }
+
+    #[test]
+    fn renamed_reexports() {
+        let folder = fixture(
+            "renamed_reexports",
+            &[
+                (
+                    "lib.rs",
+                    "pub use inner::{InnerFoo as Foo, Mode as FooMode};
+                    pub use inner::InnerFoo as OtherFoo;",
+                ),
+                (
+                    "inner.rs",
+                    "pub struct InnerFoo;
+                    impl InnerFoo {
+                        #[generate_interface(constructor)]
+                        pub fn new() -> InnerFoo { InnerFoo }
+                        #[generate_interface]
+                        pub fn copy(&self) -> Vec<InnerFoo> { vec![] }
+                        #[generate_interface]
+                        pub fn mode(&self) -> Mode { Mode::Fast }
+                    }
+                    #[generate_interface]
+                    pub enum Mode { Fast }",
+                ),
+            ],
+        );
+        let output = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for expected in [
+            "class Foo {",
+            "constructor Foo::new() -> Foo;",
+            "fn Foo::copy(&self) -> Vec<Foo>;",
+            "fn Foo::mode(&self) -> FooMode;",
+            "enum FooMode {",
+        ] {
+            assert!(output.contains(expected), "{} not in {}", expected, output);
+        }
+        assert!(!output.contains("InnerFoo"), "{}", output);
+        assert!(!output.contains("OtherFoo"), "{}", output);
+    }
+
+    #[test]
+    fn unsupported_items_are_skipped() {
+        let folder = fixture(
+            "unsupported_skipped",
+            &[(
+                "lib.rs",
+                "#[generate_interface_doc]
+                pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    pub fn bounds(&self) -> (f64, f64) { (0.0, 0.0) }
+                    #[generate_interface]
+                    pub fn width(&self) -> f64 { 0.0 }
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(out.contains("fn Foo::width(&self) -> f64;"), "{}", out);
+        assert!(!out.contains("bounds"), "{}", out);
+
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "Foo::bounds"),
+            err => panic!("expected an unsupported error, got {}", err),
+        }
+    }
+
+    #[test]
+    fn instantiated_generic_structs() {
+        let folder = fixture(
+            "instantiated",
+            &[(
+                "lib.rs",
+                "#[generate_interface_doc(instantiate(T = \"String\"))]
+                pub struct Wrapper<T> { inner: T }
+                impl<T: Clone> Wrapper<T> {
+                    #[generate_interface(constructor)]
+                    pub fn new(inner: T) -> Wrapper<T> { Wrapper { inner } }
+                    #[generate_interface]
+                    pub fn get(&self) -> T { self.inner.clone() }
+                    #[generate_interface]
+                    pub fn replace(&mut self, other: &Self) {}
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        let expected = "\tclass WrapperString {
+\t\tself_type Wrapper<String>;
+\t\tconstructor Wrapper::<String>::new(inner: String) -> Wrapper<String>;
+\t\tfn Wrapper::<String>::get(&self) -> String;
+\t\tfn Wrapper::<String>::replace(&mut self, other: &Wrapper<String>);
+\t}";
+        assert!(out.contains(expected), "{}", out);
+
+        for (name, contents, expected) in [
+            (
+                "instantiated_partially",
+                "#[generate_interface_doc(instantiate(T = \"i32\"))]
+                pub struct Pair<T, U> { a: T, b: U }",
+                "Pair",
+            ),
+            (
+                "uninstantiated",
+                "pub struct Holder<T> { inner: T }
+                impl<T> Holder<T> {
+                    #[generate_interface]
+                    pub fn get(&self) -> &T { &self.inner }
+                }",
+                "Holder::get",
+            ),
+        ] {
+            let folder = fixture(name, &[("lib.rs", contents)]);
+            let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+                .fail_on_unsupported(true)
+                .generate_to_writer(Vec::new())
+                .unwrap_err();
+            match err {
+                GenError::Unsupported { item, reason, .. } => {
+                    assert_eq!(item, expected);
+                    assert!(reason.contains("instantiate"), "{}", reason);
+                }
+                err => panic!("expected an unsupported error, got {}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn crate_root_paths() {
+        let folder = fixture(
+            "crate_root",
+            &[
+                (
+                    "net/tcp.rs",
+                    "#[generate_interface_doc]
+                    pub struct Socket;
+                    impl Socket {
+                        #[generate_interface(constructor)]
+                        pub fn new() -> Socket { Socket }
+                        #[generate_interface]
+                        pub fn close(&self) {}
+                    }",
+                ),
+                (
+                    "lib.rs",
+                    "pub mod inner {
+                        #[generate_interface]
+                        pub enum Mode { Fast }
+                    }",
+                ),
+            ],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(out.contains("self_type Socket;"), "{}", out);
+
+        let out = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder).crate_root("crate"),
+        );
+        for expected in [
+            "self_type crate::net::tcp::Socket;",
+            "constructor crate::net::tcp::Socket::new() -> Socket;",
+            "fn crate::net::tcp::Socket::close(&self);",
+            "Fast = crate::inner::Mode::Fast,",
+        ] {
+            assert!(out.contains(expected), "{}", out);
+        }
+    }
+
+    #[test]
+    fn fallible_constructors() {
+        let folder = fixture(
+            "fallible_constructors",
+            &[(
+                "lib.rs",
+                "#[generate_interface_doc]
+                pub struct Foo;
+                pub struct MyError;
+                impl Foo {
+                    #[generate_interface(constructor)]
+                    pub fn open(path: &str) -> Result<Self, std::io::Error> { Ok(Foo) }
+                    #[generate_interface(constructor)]
+                    pub fn parse(text: String) -> Result<Foo, MyError> { Ok(Foo) }
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        let expected = "\t\tself_type Foo;
+\t\tconstructor Foo::open(path: &str) -> Result<Foo, String>;
+\t\tconstructor Foo::parse(text: String) -> Result<Foo, String>;";
+        assert!(out.contains(expected), "{}", out);
+    }
+
+    #[test]
+    fn renamed_callbacks() {
+        let folder = fixture(
+            "renamed_callbacks",
+            &[(
+                "lib.rs",
+                "#[generate_interface(name = \"RustObserver\")]
+                pub trait Observer { fn notify(&self, event: i32); }
+                #[generate_interface_doc]
+                pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    pub fn subscribe(&mut self, observer: Box<dyn Observer>) {}
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for expected in [
+            "\tcallback RustObserver {\n\t\tself_type Observer;",
+            "notify = Observer::notify(&self, event: i32);",
+            "fn Foo::subscribe(&mut self, observer: Box<dyn Observer>);",
+        ] {
+            assert!(out.contains(expected), "{}", out);
+        }
+    }
+
+    #[test]
+    fn enums_from_other_files() {
+        let folder = fixture(
+            "enums_from_other_files",
+            &[
+                (
+                    "shape.rs",
+                    "use crate::color::Color;
+                    #[generate_interface_doc]
+                    pub struct Shape;
+                    impl Shape {
+                        #[generate_interface]
+                        pub fn fill(&mut self, color: Color) {}
+                        #[generate_interface]
+                        pub fn tint(&self) -> crate::color::Color { Color::Red }
+                        #[generate_interface]
+                        pub fn palette(&self) -> Vec<crate::color::Color> { vec![] }
+                    }",
+                ),
+                (
+                    "color.rs",
+                    "#[generate_interface]\npub enum Color { Red, Green }",
+                ),
+            ],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        //every annotated type is known before any signature is resolved
+        let expected = "\t\tfn Shape::fill(&mut self, color: Color);
+\t\tfn Shape::palette(&self) -> Vec<Color>;
+\t\tfn Shape::tint(&self) -> Color;";
+        assert!(out.contains(expected), "{}", out);
+        //the enum is declared before the class using it
+        assert!(out.find("enum Color") < out.find("class Shape"), "{}", out);
+    }
+
+    #[test]
+    fn only_listed_types() {
+        let folder = fixture(
+            "only_types",
+            &[(
+                "lib.rs",
+                "#[generate_interface]
+                pub enum Color { Red }
+                #[generate_interface]
+                pub trait Listener { fn on_change(&self, color: Color); }
+                #[generate_interface_doc]
+                pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    pub fn listen(&mut self, listener: Box<dyn Listener>) {}
+                }
+                #[generate_interface_doc]
+                pub struct Bar;
+                impl Bar {
+                    #[generate_interface]
+                    pub fn size(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let out = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder).only_types(&["Foo"]),
+        );
+        //the callback and the enum it uses are needed by `Foo`
+        for expected in ["class Foo", "callback Listener", "enum Color"] {
+            assert!(out.contains(expected), "{}", out);
+        }
+        assert!(!out.contains("Bar"), "{}", out);
+    }
+
+    #[test]
+    fn iterator_returns() {
+        let folder = fixture(
+            "iterator_returns",
+            &[(
+                "lib.rs",
+                "#[generate_interface_doc]
+                pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    pub fn items(&self) -> impl Iterator<Item = String> + '_ { vec![].into_iter() }
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Foo::items");
+                assert!(reason.contains("iterators_as_vec"), "{}", reason);
+            }
+            err => panic!("expected an unsupported error, got {}", err),
+        }
+
+        let out = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder).iterators_as_vec(true),
+        );
+        assert!(
+            out.contains("fn Foo::items(&self) -> Vec<String>;"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn stripped_doc_comments() {
+        let folder = fixture(
+            "stripped_docs",
+            &[(
+                "lib.rs",
+                "///A foo
+                #[generate_interface_doc]
+                pub struct Foo;
+                impl Foo {
+                    ///Takes the level
+                    #[generate_interface]
+                    pub fn set(&mut self, level: Level, data: [u8; 4]) {}
+                }
+                ///How loud
+                #[generate_interface]
+                pub enum Level {
+                    ///Barely
+                    Low = 1,
+                    High = 5,
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(out.contains("///Takes the level"), "{}", out);
+
+        let out = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder).strip_doc_comments(true),
+        );
+        assert!(!out.contains("///"), "{}", out);
+        assert!(
+            out.contains("fn Foo::set(&mut self, level: Level, data: Vec<u8>);"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn named_lifetimes() {
+        let methods = |named: bool| {
+            let lifetime = if named { "'a " } else { "" };
+            let params = if named { "<'a>" } else { "" };
+            let arg = if named { "'a, " } else { "" };
+            format!(
+                "#[generate_interface_doc]
+                pub struct Foo;
+                impl Foo {{
+                    #[generate_interface]
+                    pub fn slice{1}(&{0}self, n: usize) -> &{0}[u8] {{ &[] }}
+                    #[generate_interface]
+                    pub fn name{1}(&{0}mut self, prefix: Option<&{0}str>) -> Cow<{2}str> {{ todo!() }}
+                }}",
+                lifetime, params, arg
+            )
+        };
+        let elided = fixture("lifetimes_elided", &[("lib.rs", &methods(false))]);
+        let named = fixture("lifetimes_named", &[("lib.rs", &methods(true))]);
+        let elided = generate(Generator::new(TypeCases::Default, Language::Java, &elided));
+        let named = generate(Generator::new(TypeCases::Default, Language::Java, &named));
+        assert_eq!(named, elided);
+        assert!(
+            named.contains("fn Foo::slice(&self, n: usize) -> &[u8];"),
+            "{}",
+            named
+        );
+    }
+
+    #[test]
+    fn symbols_manifest() {
+        let folder = fixture(
+            "symbols",
+            &[
+                (
+                    "foo.rs",
+                    "#[generate_interface_doc]
+                    pub struct Foo;
+                    #[generate_interface]
+                    pub enum Level { Low, High }",
+                ),
+                (
+                    "impls.rs",
+                    "impl Foo {
+                        #[generate_interface(constructor)]
+                        pub fn new() -> Foo { Foo }
+                        #[generate_interface]
+                        pub fn set_level(&mut self, level: Level) {}
+                    }",
+                ),
+            ],
+        );
+        let manifest = folder.join("symbols.json");
+        Generator::new(TypeCases::CamelCase, Language::Java, &folder)
+            .emit_manifest(&manifest)
+            .generate_interface(folder.join("glue.in"))
+            .unwrap();
+        let json = std::fs::read_to_string(&manifest).unwrap();
+        let path = |file: &str| format!("{:?}", folder.join(file).display().to_string());
+        assert!(json.starts_with("{\n  \"version\": 1,\n"), "{}", json);
+        assert!(
+            json.contains(&format!(
+                "\"name\": \"Foo\",\n      \"foreign_name\": \"Foo\",\n      \"file\": {},",
+                path("foo.rs")
+            )),
+            "{}",
+            json
+        );
+        assert!(
+            json.contains(&format!(
+                "{{\"name\": \"set_level\", \"foreign_name\": \"setLevel\", \"constructor\": false, \"file\": {}}}",
+                path("impls.rs")
+            )),
+            "{}",
+            json
+        );
+        assert!(json.contains("\"callbacks\": [],"), "{}", json);
+        assert!(
+            json.contains("{\"name\": \"High\", \"foreign_name\": \"High\""),
+            "{}",
+            json
+        );
+
+        //checking doesn't write it
+        std::fs::remove_file(&manifest).unwrap();
+        Generator::new(TypeCases::CamelCase, Language::Java, &folder)
+            .emit_manifest(&manifest)
+            .check(folder.join("glue.in"))
+            .unwrap();
+        assert!(!manifest.exists());
+    }
+
+    #[test]
+    fn constructors_are_only_marked_ones() {
+        let folder = fixture(
+            "unmarked_new",
+            &[(
+                "lib.rs",
+                "#[generate_interface_doc]
+                pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    pub fn new() -> Foo { Foo }
+                    #[generate_interface]
+                    pub fn new_internal(size: i32) -> Self { Foo }
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        assert!(!out.contains("constructor"), "{}", out);
+        assert!(out.contains("\t\tfn Foo::new() -> Foo;"), "{}", out);
+        assert!(
+            out.contains("\t\tfn Foo::new_internal(size: i32) -> Foo;"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn clashing_method_names() {
+        let folder = fixture(
+            "overloads",
+            &[
+                (
+                    "a.rs",
+                    "#[generate_interface_doc]
+                    pub struct Foo;
+                    impl Foo {
+                        #[generate_interface]
+                        pub fn set(&mut self, x: i32) {}
+                    }",
+                ),
+                (
+                    "b.rs",
+                    "impl Foo {
+                        #[generate_interface]
+                        pub fn set(&mut self, x: &str) {}
+                        #[generate_interface(name = \"set\")]
+                        pub fn set_all(&mut self, x: Vec<i32>) {}
+                    }",
+                ),
+            ],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported {
+                file,
+                location,
+                item,
+                reason,
+            } => {
+                assert_eq!(item, "Foo::set");
+                assert_eq!(file, folder.join("b.rs"));
+                assert_eq!(location.unwrap().line, 3);
+                let declared = format!("declared at {}:5:32", folder.join("a.rs").display());
+                assert!(reason.contains(&declared), "{}", reason);
+            }
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+
+        let out = generate(
+            Generator::new(TypeCases::Default, Language::Java, &folder)
+                .disambiguate_overloads(true),
+        );
+        for line in [
+            "fn Foo::set(&mut self, x: i32);",
+            "fn Foo::set(&mut self, x: &str); alias set1;",
+            "fn Foo::set_all(&mut self, x: Vec<i32>); alias set2;",
+        ] {
+            assert!(out.contains(line), "{}", out);
+        }
+    }
+
+    #[test]
+    fn single_file() {
+        let folder = fixture(
+            "single_file",
+            &[
+                (
+                    "net/config.rs",
+                    "#[generate_interface_doc]
+                    pub struct Config;
+                    impl Config {
+                        #[generate_interface]
+                        pub fn port(&self) -> u16 { 0 }
+                    }",
+                ),
+                (
+                    "net/other.rs",
+                    "impl Config { #[generate_interface] pub fn host(&self) -> String { todo!() } }",
+                ),
+            ],
+        );
+        let out = generate(Generator::new(
+            TypeCases::Default,
+            Language::Java,
+            folder.join("net/config.rs"),
+        ));
+        assert!(out.contains("foreign_class!(\n\tclass Config {"), "{}", out);
+        assert!(out.contains("fn Config::port(&self) -> u16;"), "{}", out);
+        assert!(!out.contains("host"), "{}", out);
+    }
+
+    #[test]
+    fn deprecated_items() {
+        let folder = fixture(
+            "deprecated",
+            &[(
+                "lib.rs",
+                "#[generate_interface_doc]
+                #[deprecated]
+                pub struct Foo;
+                impl Foo {
+                    ///Old name
+                    #[generate_interface]
+                    #[deprecated(since = \"0.2\", note = \"use bar\")]
+                    pub fn foo(&self) {}
+                    #[generate_interface]
+                    #[deprecated = \"gone soon\"]
+                    pub fn baz(&self) {}
+                }",
+            )],
+        );
+        let java = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for expected in [
+            "\t///@deprecated\n\tclass Foo {",
+            "\t\t///Old name\n\t\t///@deprecated use bar\n\t\tfn Foo::foo(&self);",
+            "\t\t///@deprecated gone soon\n\t\tfn Foo::baz(&self);",
+        ] {
+            assert!(java.contains(expected), "{}", java);
+        }
+        let cpp = generate(Generator::new(TypeCases::Default, Language::Cpp, &folder));
+        assert!(
+            cpp.contains("\t\t///\\deprecated use bar\n\t\tfn Foo::foo(&self);"),
+            "{}",
+            cpp
+        );
+    }
+
+    #[test]
+    fn builder() {
+        let folder = fixture(
+            "builder",
+            &[(
+                "lib.rs",
+                "impl Foo { #[generate_interface] pub fn get_size(&self) -> i32 { 0 } }",
+            )],
+        );
+        let built = generate(
+            Generator::builder(&folder)
+                .type_case(TypeCases::SnakeCase)
+                .language(Language::Cpp)
+                .exclude("**/tests/**")
+                .build(),
+        );
+        let new = generate(
+            Generator::new(TypeCases::SnakeCase, Language::Cpp, &folder).exclude("**/tests/**"),
+        );
+        assert_eq!(built, new);
+        let defaults = generate(Generator::builder(&folder).build());
+        assert_eq!(
+            defaults,
+            generate(Generator::new(TypeCases::Default, Language::Java, &folder))
+        );
+        assert!(defaults.contains("use jni_sys::*;"), "{}", defaults);
+    }
+
+    #[test]
+    fn string_wrappers() {
+        let folder = fixture(
+            "string_wrappers",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    pub fn label(&self, prefix: Cow<str>) -> Cow<'static, str> { todo!() }
+                    #[generate_interface]
+                    pub fn hint(&self) -> Option<Cow<str>> { None }
+                    #[generate_interface]
+                    pub fn open(&mut self, path: &Path) -> PathBuf { todo!() }
+                }",
+            )],
+        );
+        let generator = || Generator::new(TypeCases::Default, Language::Java, &folder);
+        let out = generate(generator());
+        for line in [
+            "fn Foo::label(&self, prefix: String) -> String;",
+            "fn Foo::hint(&self) -> Option<String>;",
+            "fn Foo::open(&mut self, path: &Path) -> PathBuf;",
+        ] {
+            assert!(out.contains(line), "{}", out);
+        }
+        let out = generate(generator().paths_as_strings(true));
+        assert!(
+            out.contains("fn Foo::open(&mut self, path: &str) -> String;"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn method_order() {
+        let folder = fixture(
+            "method_order",
+            &[
+                (
+                    "b.rs",
+                    "impl Foo {
+                        #[generate_interface]
+                        pub fn zoom(&self) {}
+                        #[generate_interface]
+                        pub fn create() -> Foo { Foo }
+                        #[generate_interface]
+                        pub fn apply(&self) {}
+                    }",
+                ),
+                (
+                    "a.rs",
+                    "#[generate_interface_doc]
+                    pub struct Foo;
+                    impl Foo {
+                        #[generate_interface]
+                        pub fn middle(&self) {}
+                        #[generate_interface(constructor)]
+                        pub fn new() -> Foo { Foo }
+                    }",
+                ),
+            ],
+        );
+        let methods = |order: Order| {
+            let out =
+                generate(Generator::new(TypeCases::Default, Language::Java, &folder).order(order));
+            out.lines()
+                .filter_map(|it| it.trim().split("Foo::").nth(1))
+                .filter_map(|it| it.split('(').next())
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+        for (order, expected) in [
+            (Order::Sorted, ["new", "create", "apply", "middle", "zoom"]),
+            (
+                Order::SourceDeclaration,
+                ["new", "middle", "zoom", "create", "apply"],
+            ),
+        ] {
+            let first = methods(order);
+            assert_eq!(first, expected);
+            assert_eq!(methods(order), first);
+        }
+    }
+
+    #[test]
+    fn inherited_callback_methods() {
+        let folder = fixture(
+            "supertraits",
+            &[
+                (
+                    "lib.rs",
+                    "#[generate_interface]
+                    pub trait Observer { fn notify(&self, event: i32); }
+                    #[generate_interface]
+                    pub trait ExtendedObserver: Observer + Send { fn on_error(&self, msg: &str); }",
+                ),
+                (
+                    "logging.rs",
+                    "#[generate_interface]
+                    pub trait LoggingObserver: crate::ExtendedObserver + 'static {
+                        fn log(&self, line: String);
+                    }",
+                ),
+            ],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        let callback = |name: &str| {
+            let start = out.find(&format!("callback {} {{", name)).unwrap();
+            out[start..start + out[start..].find("}").unwrap()].to_string()
+        };
+        let extended = callback("ExtendedObserver");
+        for expected in [
+            "notify = Observer::notify(&self, event: i32);",
+            "on_error = ExtendedObserver::on_error(&self, msg: &str);",
+        ] {
+            assert!(extended.contains(expected), "{}", out);
+        }
+        let logging = callback("LoggingObserver");
+        for expected in [
+            "notify = Observer::notify(&self, event: i32);",
+            "on_error = ExtendedObserver::on_error(&self, msg: &str);",
+            "log = LoggingObserver::log(&self, line: String);",
+        ] {
+            assert!(logging.contains(expected), "{}", out);
+        }
+        assert_eq!(callback("Observer").matches(" = ").count(), 1, "{}", out);
+    }
+
+    #[test]
+    fn clean_split_output() {
+        let type_with = |name: &str| {
+            format!(
+                "impl {} {{ #[generate_interface] pub fn get(&self) -> i32 {{ 0 }} }}",
+                name
+            )
+        };
+        let both = format!("{}\n{}", type_with("Foo"), type_with("Bar"));
+        let folder = fixture("clean_split", &[("lib.rs", &both)]);
+        let dir = folder.with_extension("split");
+        let _ = std::fs::remove_dir_all(&dir);
+        let split = || {
+            Generator::new(TypeCases::Default, Language::Java, &folder)
+                .clean(true)
+                .generate_interface_split(&dir)
+                .unwrap()
+        };
+        split();
+        assert!(dir.join("Bar.in").exists());
+        std::fs::write(dir.join("notes.in"), "kept").unwrap();
+        //an edited index can't make it remove other files
+        let mut index = std::fs::read_to_string(dir.join("index.txt")).unwrap();
+        index.push_str("../lib.rs\nnotes.txt\n");
+        std::fs::write(dir.join("index.txt"), index).unwrap();
+        std::fs::write(folder.join("lib.rs"), type_with("Foo")).unwrap();
+
+        split();
+        assert!(!dir.join("Bar.in").exists());
+        assert!(dir.join("Foo.in").exists());
+        assert!(dir.join("notes.in").exists());
+        assert!(folder.join("lib.rs").exists());
+    }
+
+    #[test]
+    fn primitive_mappings() {
+        let folder = fixture(
+            "primitive_mappings",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    pub fn len(&self, from: usize) -> usize { 0 }
+                    #[generate_interface]
+                    pub fn id(&self) -> u32 { 0 }
+                }",
+            )],
+        );
+        let generator = Generator::new(TypeCases::Default, Language::Java, &folder)
+            .with_type_map("usize", "i64");
+        //only mapped when asked to since the glue needs a conversion
+        assert_eq!(
+            generator.mapped_type("std::time::Duration").as_deref(),
+            Some("std::time::Duration")
+        );
+        let generator = generator.with_type_map("Duration", "u64");
+        assert_eq!(generator.mapped_type("usize").as_deref(), Some("i64"));
+        assert_eq!(
+            generator.mapped_type("Vec<u32>").as_deref(),
+            Some("Vec<u32>")
+        );
+        assert_eq!(
+            generator.mapped_type("std::time::Duration").as_deref(),
+            Some("u64")
+        );
+        let out = generate(generator);
+        assert!(
+            out.contains("fn Foo::len(&self, from: i64) -> i64;"),
+            "{}",
+            out
+        );
+        assert!(out.contains("fn Foo::id(&self) -> u32;"), "{}", out);
+        let cpp = Generator::new(TypeCases::Default, Language::Cpp, &folder);
+        assert_eq!(cpp.mapped_type("SystemTime"), None);
+    }
+
+    #[test]
+    fn raw_identifiers() {
+        let folder = fixture(
+            "raw_identifiers",
+            &[(
+                "lib.rs",
+                "impl Foo {
+                    #[generate_interface]
+                    pub fn r#match(&self, r#type: i32) -> bool { true }
+                    #[generate_interface]
+                    pub fn r#async(&self, r#in_use: bool) {}
+                }",
+            )],
+        );
+        let default = generate(Generator::new(TypeCases::Default, Language::Java, &folder));
+        for line in [
+            "fn Foo::r#match(&self, r#type: i32) -> bool; alias match;",
+            "fn Foo::r#async(&self, in_use: bool); alias async;",
+        ] {
+            assert!(default.contains(line), "{}", default);
+        }
+        let camel = generate(Generator::new(
+            TypeCases::CamelCase,
+            Language::Java,
+            &folder,
+        ));
+        assert!(
+            camel.contains("fn Foo::r#async(&self, inUse: bool); alias async;"),
+            "{}",
+            camel
+        );
+    }
+
+    #[test]
+    fn value_structs() {
+        let root = fixture(
+            "value_structs",
+            &[(
+                "lib.rs",
+                "#[repr(C)]
+                #[generate_interface(value)]
+                pub struct Point {
+                    pub x: f64,
+                    pub y: f64,
+                }
+                #[generate_interface(value)]
+                pub struct Label {
+                    pub text: String,
+                    pub tags: Vec<String>,
+                    hidden: bool,
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::CamelCase, Language::Java, &root));
+        assert!(out.contains("foreign_class!(\n\tclass Point {"));
+        assert!(out.contains("\t\tconstructor Point::new(x: f64, y: f64) -> Point;"));
+        assert!(out.contains("\t\tfn Point::get_x(&self) -> f64; alias getX;"));
+        assert!(out.contains("\t\tfn Point::get_y(&self) -> f64; alias getY;"));
+        assert!(out.contains("\t\tfn Point::set_x(&mut self, x: f64); alias setX;"));
+        assert!(out.contains("\t\tfn Label::get_text(&self) -> String; alias getText;"));
+        assert!(!out.contains("get_tags"));
+        assert!(!out.contains("get_hidden"));
+        assert!(!out.contains("Label::new"));
+        let err = Generator::new(TypeCases::CamelCase, Language::Java, &root)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("Label::tags"));
+    }
+
+    #[test]
+    fn class_prefix() {
+        let root = fixture(
+            "class_prefix",
+            &[(
+                "lib.rs",
+                "pub struct Config;
+                impl Config {
+                    #[generate_interface(constructor)]
+                    fn new() -> Config { Config }
+                }
+                pub struct Client;
+                impl Client {
+                    #[generate_interface]
+                    fn configure(&mut self, config: &Config) {}
+                }
+                #[generate_interface]
+                pub trait Listener {
+                    fn on_event(&self);
+                }
+                #[generate_interface]
+                pub enum Mode {
+                    Fast,
+                }",
+            )],
+        );
+        let out =
+            generate(Generator::new(TypeCases::Default, Language::Java, &root).class_prefix("Net"));
+        for line in [
+            "\tclass NetConfig {\n\t\tself_type Config;",
+            "\tclass NetClient {",
+            "\t\tfn Client::configure(&mut self, config: &Config);",
+            "\tcallback NetListener {",
+            "\tenum NetMode {",
+        ] {
+            assert!(out.contains(line), "{} not in {}", line, out);
+        }
+        let interface = Generator::new(TypeCases::Default, Language::Java, &root)
+            .class_prefix("Net")
+            .collect()
+            .unwrap();
+        let config = interface
+            .classes
+            .iter()
+            .find(|it| it.name == "Config")
+            .unwrap();
+        assert_eq!(config.class_name(TypeCases::Default), "NetConfig");
+    }
+
+    #[test]
+    fn async_methods() {
+        let root = fixture(
+            "async_methods",
+            &[(
+                "lib.rs",
+                "pub struct Client;
+                impl Client {
+                    #[generate_interface]
+                    async fn fetch(&self, url: &str) -> String { todo!() }
+                    #[generate_interface]
+                    fn close(&self) {}
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        assert!(!out.contains("fetch"));
+        assert!(out.contains("\t\tfn Client::close(&self);"));
+        let err = Generator::new(TypeCases::Default, Language::Java, &root)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "Client::fetch"),
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+
+        let out = generate(
+            Generator::new(TypeCases::Default, Language::Java, &root).async_as_blocking(true),
+        );
+        assert!(out.contains("\t\t///Blocks until the rust future completes\n\t\tfn Client::fetch(&self, url: &str) -> String;"), "{}", out);
+    }
+
+    #[test]
+    fn async_free_functions() {
+        let root = fixture(
+            "async_free_functions",
+            &[(
+                "lib.rs",
+                "#[generate_interface]
+                pub async fn free_async() -> i32 { 0 }",
+            )],
+        );
+        let generator =
+            || Generator::new(TypeCases::Default, Language::Java, &root).free_fn_class("Lib");
+        assert!(!generate(generator()).contains("free_async"));
+        let err = generator()
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "free_async");
+                assert!(reason.contains("async_as_blocking"), "{}", reason);
+            }
+            _ => panic!("expected an unsupported error, got {}", err),
+        }
+        let out = generate(generator().async_as_blocking(true));
+        assert!(
+            out.contains(
+                "\t\t///Blocks until the rust future completes\n\t\tfn free_async() -> i32;"
+            ),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn doc_attributes() {
+        let root = fixture(
+            "doc_attributes",
+            &[(
+                "lib.rs",
+                r#"pub struct Foo;
+                impl Foo {
+                    ///Loads the file
+                    #[doc = "Fails when it's missing"]
+                    #[doc = "first\nsecond"]
+                    #[doc(alias = "open")]
+                    #[generate_interface]
+                    fn load(&self) {}
+                }"#,
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        assert!(
+            out.contains(
+                "\t\t///Loads the file\n\t\t///Fails when it's missing\n\t\t///first\n\t\t///second\n\t\tfn Foo::load(&self);"
+            ),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn callback_class_params() {
+        let root = fixture(
+            "callback_class_params",
+            &[
+                ("lib.rs", "mod model; mod listener; mod net; mod ui;"),
+                (
+                    "model.rs",
+                    "pub struct Foo;
+                    impl Foo {
+                        #[generate_interface]
+                        fn name(&self) -> String { todo!() }
+                    }
+                    pub struct Other;
+                    impl Other {
+                        #[generate_interface]
+                        fn name(&self) -> String { todo!() }
+                    }",
+                ),
+                ("net.rs", "pub struct Config; impl Config { #[generate_interface] fn port(&self) -> u16 { 0 } }"),
+                ("ui.rs", "pub struct Config; impl Config { #[generate_interface] fn theme(&self) {} }"),
+                (
+                    "listener.rs",
+                    "use crate::model::{Foo, Other as Renamed};
+                    use crate::net::Config;
+                    #[generate_interface]
+                    pub trait Listener {
+                        fn notify(&self, source: &Foo);
+                        fn renamed(&self, source: &Renamed);
+                        fn configured(&self, config: &Config);
+                        fn forward(&self, other: &dyn Listener);
+                    }",
+                ),
+            ],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        for line in [
+            "\t\tnotify = Listener::notify(&self, source: &Foo);",
+            "\t\trenamed = Listener::renamed(&self, source: &Other);",
+            "\t\tconfigured = Listener::configured(&self, config: &net::Config);",
+            "\t\tforward = Listener::forward(&self, other: &dyn Listener);",
+        ] {
+            assert!(out.contains(line), "{} not in {}", line, out);
+        }
+        //the classes are written before the callback using them
+        let callback = out.find("callback Listener").unwrap();
+        for class in ["class Foo", "class Other", "class NetConfig"] {
+            assert!(
+                out.find(class).unwrap() < callback,
+                "{} is after the callback",
+                class
+            );
+        }
+    }
+
+    #[test]
+    fn multiple_constructors() {
+        let root = fixture(
+            "multiple_constructors",
+            &[(
+                "lib.rs",
+                "pub struct Foo;
+                impl Foo {
+                    #[generate_interface(constructor)]
+                    fn new() -> Foo { Foo }
+                    #[generate_interface(constructor)]
+                    fn open(path: &str) -> Foo { Foo }
+                }",
+            )],
+        );
+        let generator = || Generator::new(TypeCases::Default, Language::Java, &root);
+        let out = generate(generator().multiple_constructors(ConstructorPolicy::Static));
+        assert!(
+            out.contains("\t\tconstructor Foo::new() -> Foo;"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains("\t\tfn Foo::open(path: &str) -> Foo;"),
+            "{}",
+            out
+        );
+        assert_eq!(out.matches("constructor ").count(), 1);
+
+        let out = generate(generator().multiple_constructors(ConstructorPolicy::Reject));
+        assert!(!out.contains("open"));
+        let err = generator()
+            .multiple_constructors(ConstructorPolicy::Reject)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Foo::open");
+                assert!(reason.contains("`Foo::new` declared at"), "{}", reason);
+            }
+            err => panic!("{}", err),
+        }
+        assert_eq!(generate(generator()).matches("constructor ").count(), 2);
+    }
+
+    #[test]
+    fn file_extensions() {
+        let root = fixture(
+            "file_extensions",
+            &[
+                (
+                    "lib.rs",
+                    "pub struct Foo; impl Foo { #[generate_interface] fn id(&self) -> i32 { 0 } }",
+                ),
+                (
+                    "net.rs.tmpl",
+                    "pub struct Client; impl Client { #[generate_interface] fn connect(&self) {} }",
+                ),
+                ("notes.txt", "not rust"),
+            ],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        assert!(out.contains("class Foo"));
+        assert!(!out.contains("Client"));
+        let out = generate(
+            Generator::new(TypeCases::Default, Language::Java, &root)
+                .extensions(&["rs", "rs.tmpl"]),
+        );
+        assert!(out.contains("\t\tfn Client::connect(&self);"), "{}", out);
+    }
+
+    #[test]
+    fn dry_run_summary() {
+        let root = fixture(
+            "dry_run_summary",
+            &[(
+                "lib.rs",
+                "pub struct Foo;
+                impl Foo {
+                    #[generate_interface(constructor)]
+                    fn new() -> Foo { Foo }
+                    #[generate_interface]
+                    fn id(&self) -> i32 { 0 }
+                    #[generate_interface]
+                    const RATIO: f64 = 0.5;
+                }
+                pub struct Bar;
+                impl Bar {
+                    #[generate_interface]
+                    fn name(&self) -> String { todo!() }
+                }
+                #[generate_interface]
+                pub trait Listener {
+                    fn on_event(&self, id: i32);
+                }
+                #[generate_interface]
+                pub enum Mode { Fast, Slow }
+                #[generate_interface]
+                pub enum Level { Low }",
+            )],
+        );
+        let summary = Generator::new(TypeCases::Default, Language::Java, &root)
+            .dry_run_summary()
+            .unwrap();
+        assert_eq!(
+            (
+                summary.classes,
+                summary.methods,
+                summary.enums,
+                summary.traits
+            ),
+            (2, 4, 2, 1)
+        );
+        assert_eq!(summary.skipped.len(), 1);
+        assert!(
+            summary.skipped[0].contains("Foo::RATIO"),
+            "{:?}",
+            summary.skipped
+        );
+    }
+
+    #[test]
+    fn dry_run_summary_with_only() {
+        let root = fixture(
+            "dry_run_summary_with_only",
+            &[(
+                "lib.rs",
+                "pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    fn id(&self) -> i32 { 0 }
+                    #[generate_interface(only = \"java\")]
+                    fn java_id(&self) -> i32 { 0 }
+                    #[generate_interface(only = \"cpp\")]
+                    fn cpp_id(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let methods = |language| {
+            Generator::new(TypeCases::Default, language, &root)
+                .dry_run_summary()
+                .unwrap()
+                .methods
+        };
+        assert_eq!(methods(Language::Java), 2);
+        assert_eq!(methods(Language::Kotlin), 2);
+        assert_eq!(methods(Language::Python), 1);
+    }
+
+    #[test]
+    fn marker_fields() {
+        let root = fixture(
+            "marker_fields",
+            &[(
+                "lib.rs",
+                "use std::marker::PhantomData;
+                #[generate_interface(value)]
+                pub struct Meters {
+                    pub value: f64,
+                    pub unit: PhantomData<Unit>,
+                }
+                #[generate_access_methods]
+                struct Pinned {
+                    id: i32,
+                    _pin: std::marker::PhantomPinned,
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        assert!(
+            out.contains("\t\tconstructor Meters::new(value: f64) -> Meters;"),
+            "{}",
+            out
+        );
+        assert!(out.contains("\t\tfn Meters::get_value(&self) -> f64;"));
+        assert!(
+            out.contains("\t\tconstructor Pinned::new(id: i32) -> Pinned;"),
+            "{}",
+            out
+        );
+        assert!(!out.contains("unit"));
+        assert!(!out.contains("_pin"));
+        Generator::new(TypeCases::Default, Language::Java, &root)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap();
+    }
+
+    #[test]
+    fn language_only_methods() {
+        let root = fixture(
+            "language_only_methods",
+            &[(
+                "lib.rs",
+                "pub struct Foo;
+                impl Foo {
+                    #[generate_interface(only = \"java\")]
+                    fn attach(&self) {}
+                    #[generate_interface(only = \"cpp\", only = \"python\")]
+                    fn handle(&self) -> u64 { 0 }
+                    #[generate_interface(only = \"cpp\")]
+                    const SIZE: u32 = 4;
+                    #[generate_interface]
+                    fn id(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let out = |language| generate(Generator::new(TypeCases::Default, language, &root));
+        let java = out(Language::Java);
+        assert!(java.contains("\t\tfn Foo::attach(&self);"), "{}", java);
+        assert!(java.contains("\t\tfn Foo::id(&self) -> i32;"));
+        assert!(!java.contains("handle") && !java.contains("SIZE"));
+        assert!(out(Language::Kotlin).contains("fn Foo::attach(&self)"));
+        let cpp = out(Language::Cpp);
+        assert!(!cpp.contains("attach"), "{}", cpp);
+        assert!(cpp.contains("\t\tfn Foo::handle(&self) -> u64;"));
+        assert!(cpp.contains("fn SIZE() -> u32 { Foo::SIZE }"));
+        assert!(cpp.contains("\t\tfn Foo::id(&self) -> i32;"));
+    }
+
+    #[test]
+    fn nested_generics() {
+        let root = fixture(
+            "nested_generics",
+            &[(
+                "lib.rs",
+                "use std::time::{Duration, SystemTime};
+                pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    fn all(&self) -> Option<Vec<Self>> { None }
+                    #[generate_interface]
+                    fn maybe(&self) -> Vec<Option<crate::Foo>> { vec![] }
+                    #[generate_interface]
+                    fn load(&self) -> Result<Vec<Foo>, String> { todo!() }
+                    #[generate_interface]
+                    fn set(&mut self, ids: Option<Vec<i32>>) {}
+                    #[generate_interface]
+                    fn timeouts(&self) -> Option<Vec<Duration>> { None }
+                }
+                pub struct Clock;
+                impl Clock {
+                    #[generate_interface]
+                    fn history(&self) -> Result<Vec<Option<SystemTime>>, String> { todo!() }
+                }",
+            )],
+        );
+        let out = generate(
+            Generator::new(TypeCases::Default, Language::Java, &root)
+                .with_type_map("Duration", "u64"),
+        );
+        for line in [
+            "fn Foo::all(&self) -> Option<Vec<Foo>>;",
+            "fn Foo::maybe(&self) -> Vec<Option<Foo>>;",
+            "fn Foo::load(&self) -> Result<Vec<Foo>, String>;",
+            "fn Foo::set(&mut self, ids: Option<Vec<i32>>);",
+            "fn Foo::timeouts(&self) -> Option<Vec<u64>>;",
+            "fn Clock::history(&self) -> Result<Vec<Option<SystemTime>>, String>;",
+        ] {
+            assert!(out.contains(line), "{} not in {}", line, out);
+        }
+        //a layer the language has no counterpart for makes the whole signature unsupported
+        let err = Generator::new(TypeCases::Default, Language::Cpp, &root)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "Clock::history"),
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn assert_interface_up_to_date() {
+        let root = fixture(
+            "assert_up_to_date",
+            &[(
+                "lib.rs",
+                "pub struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    fn a(&self) {}
+                    #[generate_interface]
+                    fn b(&self) {}
+                }",
+            )],
+        );
+        let generator = || Generator::new(TypeCases::Default, Language::Java, &root);
+        let file = root.join("interface.in");
+        std::fs::write(&file, generate(generator())).unwrap();
+        crate::assert_interface_up_to_date(generator(), &file);
+
+        let stale = generate(generator()).replace("fn Foo::b(&self);", "fn Foo::c(&self);");
+        std::fs::write(&file, stale).unwrap();
+        let panic =
+            std::panic::catch_unwind(|| crate::assert_interface_up_to_date(generator(), &file))
+                .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(
+            message.contains("interface.in is out of date"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("    7 - \t\tfn Foo::c(&self);\n      + \t\tfn Foo::b(&self);"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn pointer_width() {
+        let root = fixture(
+            "pointer_width",
+            &[(
+                "lib.rs",
+                "pub struct Buffer;
+                impl Buffer {
+                    #[generate_interface]
+                    fn len(&self) -> usize { 0 }
+                    #[generate_interface]
+                    fn seek(&mut self, offset: isize) {}
+                }",
+            )],
+        );
+        let generator =
+            |width| Generator::new(TypeCases::Default, Language::Java, &root).pointer_width(width);
+        let out = generate(generator(PointerWidth::W64));
+        assert!(out.contains("\t\tfn Buffer::len(&self) -> u64;"), "{}", out);
+        assert!(out.contains("\t\tfn Buffer::seek(&mut self, offset: i64);"));
+        let out = generate(generator(PointerWidth::W32));
+        assert!(out.contains("\t\tfn Buffer::len(&self) -> u32;"), "{}", out);
+        assert!(out.contains("\t\tfn Buffer::seek(&mut self, offset: i32);"));
+        assert!(
+            generate(generator(PointerWidth::Native)).contains("fn Buffer::len(&self) -> usize;")
+        );
+        assert_eq!(
+            generator(PointerWidth::W32).mapped_type("usize").as_deref(),
+            Some("u32")
+        );
+        //explicit mappings take precedence
+        let mapped = generator(PointerWidth::W32).with_type_map("usize", "i64");
+        assert_eq!(mapped.mapped_type("usize").as_deref(), Some("i64"));
+    }
+
+    #[test]
+    fn free_functions() {
+        let root = fixture(
+            "free_functions",
+            &[
+                (
+                    "lib.rs",
+                    "mod net;
+                    ///Sets up logging
+                    #[generate_interface]
+                    pub fn init_library(level: i32) {}",
+                ),
+                (
+                    "net.rs",
+                    "#[generate_interface] pub fn connect(host: &str) -> bool { true }",
+                ),
+            ],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &root)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, .. } => assert_eq!(item, "init_library"),
+            err => panic!("{}", err),
+        }
+        let out = generate(
+            Generator::new(TypeCases::CamelCase, Language::Java, &root).free_fn_class("Lib"),
+        );
+        assert!(
+            out.contains(
+                "\tclass Lib {\n\t\tfn net::connect(host: &str) -> bool; alias connect;\n\t\t\
+                 ///Sets up logging\n\t\tfn init_library(level: i32); alias initLibrary;\n\t}"
+            ),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn where_clauses() {
+        let root = fixture(
+            "where_clauses",
+            &[(
+                "lib.rs",
+                "struct Task;
+                impl Task {
+                    #[generate_interface(generic(T = \"String\"))]
+                    pub fn set_name<T>(&mut self, name: T) where T: Into<String> {}
+                    #[generate_interface]
+                    pub fn size(&self) -> usize where Self: Sized { 0 }
+                }
+                #[generate_interface]
+                trait Listener {
+                    fn on_event(&self, code: i32);
+                    fn boxed(self) -> Box<dyn Listener> where Self: Sized + 'static;
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        assert!(
+            out.contains("fn Task::set_name(&mut self, name: String);"),
+            "{}",
+            out
+        );
+        assert!(out.contains("fn Task::size(&self) -> usize;"), "{}", out);
+        assert!(
+            out.contains("on_event = Listener::on_event(&self, code: i32);"),
+            "{}",
+            out
+        );
+        assert!(!out.contains("boxed"), "{}", out);
+
+        let run = fixture(
+            "where_clauses_unhinted",
+            &[(
+                "lib.rs",
+                "struct Task;
+                impl Task {
+                    #[generate_interface]
+                    pub fn run<F>(&self, f: F) where F: Fn() {}
+                }",
+            )],
+        );
+        let err = Generator::new(TypeCases::Default, Language::Java, &run)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Task::run");
+                assert!(reason.contains("generic(F = "), "{}", reason);
+            }
+            err => panic!("{}", err),
+        }
+        let err = Generator::new(TypeCases::Default, Language::Java, &root)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Listener::boxed");
+                assert!(reason.contains("Self: Sized"), "{}", reason);
+            }
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn settings() {
+        let root = fixture(
+            "settings",
+            &[(
+                "lib.rs",
+                "struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    fn get_value(&self) -> usize { 0 }
+                }",
+            )],
+        );
+        let configured = || {
+            Generator::new(TypeCases::CamelCase, Language::Kotlin, &root)
+                .pointer_width(PointerWidth::W64)
+                .with_type_map("Uuid", "String")
+                .with_cfg("feature", "extra")
+                .exclude("**/tests/**")
+        };
+        assert_eq!(configured().settings(), configured().settings());
+        assert_ne!(
+            configured().settings(),
+            configured().with_cfg("unix", "").settings()
+        );
+        let settings = configured().settings().clone();
+        assert!(format!("{:?}", settings).contains("Kotlin"));
+        assert_eq!(
+            generate(Generator::with_settings(settings)),
+            generate(configured())
+        );
+    }
+
+    #[test]
+    fn transparent_newtypes() {
+        let root = fixture(
+            "transparent_newtypes",
+            &[(
+                "lib.rs",
+                "#[generate_interface(transparent)]
+                pub struct Meters(f64);
+                #[generate_interface(transparent)]
+                pub struct Point(f64, f64);
+                struct Route;
+                impl Route {
+                    #[generate_interface]
+                    fn length(&self) -> Meters { Meters(0.0) }
+                    #[generate_interface]
+                    fn legs(&self, min: Meters) -> Vec<Meters> { vec![] }
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        assert!(out.contains("fn Route::length(&self) -> f64;"), "{}", out);
+        assert!(
+            out.contains("fn Route::legs(&self, min: f64) -> Vec<f64>;"),
+            "{}",
+            out
+        );
+        assert!(!out.contains("class Meters"), "{}", out);
+        let err = Generator::new(TypeCases::Default, Language::Java, &root)
+            .fail_on_unsupported(true)
+            .generate_to_writer(Vec::new())
+            .unwrap_err();
+        match err {
+            GenError::Unsupported { item, reason, .. } => {
+                assert_eq!(item, "Point");
+                assert!(reason.contains("exactly one unnamed field"), "{}", reason);
+            }
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn language_type_maps() {
+        let root = fixture(
+            "language_type_maps",
+            &[(
+                "lib.rs",
+                "struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    fn is_ready(&self, force: bool) -> bool { force }
+                }",
+            )],
+        );
+        let configured = |language| {
+            Generator::new(TypeCases::Default, language, &root)
+                .with_type_map_for(Language::Cpp, "bool", "Bool")
+                .with_type_map("Uuid", "String")
+        };
+        let cpp = generate(configured(Language::Cpp));
+        assert!(
+            cpp.contains("fn Foo::is_ready(&self, force: Bool) -> Bool;"),
+            "{}",
+            cpp
+        );
+        let java = generate(configured(Language::Java));
+        assert!(
+            java.contains("fn Foo::is_ready(&self, force: bool) -> bool;"),
+            "{}",
+            java
+        );
+        assert_eq!(
+            configured(Language::Cpp).mapped_type("Uuid").unwrap(),
+            "String"
+        );
+        assert_eq!(
+            configured(Language::Cpp)
+                .with_type_map("bool", "u8")
+                .mapped_type("bool")
+                .unwrap(),
+            "Bool"
+        );
+    }
+
+    #[test]
+    fn variant_docs() {
+        let root = fixture(
+            "variant_docs",
+            &[(
+                "lib.rs",
+                "#[generate_interface]
+                enum Level {
+                    /// low priority
+                    Low,
+                    #[doc = \" high priority\"]
+                    High,
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        assert!(
+            out.contains("\t\t/// low priority\n\t\tLow = Level::Low,\n\t\t/// high priority\n\t\tHigh = Level::High,"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn append_between_markers() {
+        let root = fixture(
+            "append_between_markers",
+            &[(
+                "lib.rs",
+                "struct Foo;
+                impl Foo {
+                    #[generate_interface]
+                    fn get_value(&self) -> i32 { 0 }
+                }",
+            )],
+        );
+        let path = root.join("glue.in");
+        let preamble = "foreign_typemap!(\n\t($p:r_type) Uuid => String {}\n);\n// RIFGEN:BEGIN\n";
+        let epilogue = "// RIFGEN:END\n//written by hand\n";
+        std::fs::write(&path, format!("{}stale\n{}", preamble, epilogue)).unwrap();
+        let generator = || Generator::new(TypeCases::Default, Language::Java, &root).append(true);
+        assert!(generator().check(&path).is_err());
+        generator().generate_interface(&path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with(preamble), "{}", written);
+        assert!(written.ends_with(epilogue), "{}", written);
+        assert!(!written.contains("stale"), "{}", written);
+        assert!(
+            written.contains("fn Foo::get_value(&self) -> i32;"),
+            "{}",
+            written
+        );
+        generator().check(&path).unwrap();
+
+        //a begin marker ending the file starts an empty region
+        std::fs::write(&path, "preamble\n// RIFGEN:BEGIN").unwrap();
+        generator().generate_interface(&path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            written.starts_with("preamble\n// RIFGEN:BEGIN\n"),
+            "{}",
+            written
+        );
+        assert!(written.ends_with("\t}\n);\n// RIFGEN:END\n"), "{}", written);
+        generator().check(&path).unwrap();
+
+        //malformed markers are an error and the file is kept
+        for malformed in [
+            "preamble\n// RIFGEN:BEGIN\nstale\n",
+            "// RIFGEN:END\npreamble\n",
+            "// RIFGEN:END\n// RIFGEN:BEGIN\n",
+        ] {
+            std::fs::write(&path, malformed).unwrap();
+            let err = generator().generate_interface(&path).unwrap_err();
+            assert!(matches!(err, GenError::Markers { .. }), "{}", err);
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), malformed);
+        }
+
+        //without markers the whole file is written
+        std::fs::write(&path, "stale\n").unwrap();
+        generator().generate_interface(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            generate(Generator::new(TypeCases::Default, Language::Java, &root))
+        );
+    }
+
+    #[test]
+    fn receivers() {
+        let root = fixture(
+            "receivers",
+            &[(
+                "lib.rs",
+                "struct Counter;
+                impl Counter {
+                    #[generate_interface]
+                    fn value(&self) -> i32 { 0 }
+                    #[generate_interface]
+                    fn increment(&mut self) {}
+                    #[generate_interface]
+                    fn finish(self) -> i32 { 0 }
+                    #[generate_interface]
+                    fn reset(mut self) -> Counter { self }
+                }",
+            )],
+        );
+        let out = generate(Generator::new(TypeCases::Default, Language::Java, &root));
+        for line in [
+            "fn Counter::value(&self) -> i32;",
+            "fn Counter::increment(&mut self);",
+            "fn Counter::finish(self) -> i32;",
+            "fn Counter::reset(self) -> Counter;",
+        ] {
+            assert!(out.contains(line), "{}", out);
+        }
+    }
+}
@@ -1 +1,860 @@
+use crate::enums::Types;
+use crate::Language;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use syn::__private::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::{GenericArgument, PathArguments, Token, TypeParamBound};
 
+///A type as it appears in a method signature.
+/// Unlike `syn::Type` it is written back without the extra spaces `to_token_stream` adds
+/// ie `Option<Foo>` instead of `Option < Foo >`
+#[derive(Debug, Clone, PartialEq)]
+pub enum RustType {
+    /// `i32`, `Foo`, `Option<Foo>` or `std::time::Duration`
+    Path {
+        segments: Vec<String>,
+        /// generic arguments of the last segment
+        args: Vec<RustType>,
+    },
+    /// `&T`, `&'a T` or `&mut T`
+    Reference {
+        lifetime: Option<String>,
+        mutable: bool,
+        inner: Box<RustType>,
+    },
+    /// `[T]`
+    Slice(Box<RustType>),
+    /// `[T; N]`
+    Array(Box<RustType>, String),
+    /// `(A, B)` or `()`
+    Tuple(Vec<RustType>),
+    /// `dyn Trait + 'static`
+    TraitObject(Vec<RustType>),
+    /// `impl Iterator<Item = T>`, the item type
+    Iterator(Box<RustType>),
+    /// A lifetime used as a generic argument or bound
+    Lifetime(String),
+    /// Anything else is written as is
+    Other(String),
+}
+
+impl RustType {
+    pub(crate) fn from_syn(ty: &syn::Type) -> RustType {
+        match ty {
+            syn::Type::Path(val) if val.qself.is_none() => {
+                let segments = val
+                    .path
+                    .segments
+                    .iter()
+                    .map(|it| it.ident.to_string())
+                    .collect();
+                let args = match val.path.segments.last().map(|it| &it.arguments) {
+                    Some(PathArguments::AngleBracketed(val)) => val
+                        .args
+                        .iter()
+                        .map(|it| match it {
+                            GenericArgument::Type(ty) => RustType::from_syn(ty),
+                            GenericArgument::Lifetime(val) => RustType::Lifetime(val.to_string()),
+                            other => RustType::Other(other.to_token_stream().to_string()),
+                        })
+                        .collect(),
+                    _ => vec![],
+                };
+                RustType::Path { segments, args }
+            }
+            syn::Type::Reference(val) => RustType::Reference {
+                lifetime: val.lifetime.as_ref().map(|it| it.to_string()),
+                mutable: val.mutability.is_some(),
+                inner: Box::new(RustType::from_syn(&val.elem)),
+            },
+            syn::Type::Slice(val) => RustType::Slice(Box::new(RustType::from_syn(&val.elem))),
+            syn::Type::Array(val) => RustType::Array(
+                Box::new(RustType::from_syn(&val.elem)),
+                val.len.to_token_stream().to_string(),
+            ),
+            syn::Type::Tuple(val) => {
+                RustType::Tuple(val.elems.iter().map(RustType::from_syn).collect())
+            }
+            syn::Type::TraitObject(val) => RustType::TraitObject(
+                val.bounds
+                    .iter()
+                    .map(|it| match it {
+                        TypeParamBound::Trait(bound) => {
+                            RustType::from_syn(&syn::Type::Path(syn::TypePath {
+                                qself: None,
+                                path: bound.path.clone(),
+                            }))
+                        }
+                        TypeParamBound::Lifetime(val) => RustType::Lifetime(val.to_string()),
+                    })
+                    .collect(),
+            ),
+            syn::Type::ImplTrait(val) if iterator_item(&val.bounds).is_some() => {
+                RustType::Iterator(Box::new(RustType::from_syn(
+                    iterator_item(&val.bounds).unwrap(),
+                )))
+            }
+            syn::Type::Paren(val) => RustType::from_syn(&val.elem),
+            syn::Type::Group(val) => RustType::from_syn(&val.elem),
+            other => RustType::Other(other.to_token_stream().to_string()),
+        }
+    }
+
+    ///The last segment of a path type ie `Duration` for `std::time::Duration`
+    pub fn name(&self) -> Option<&String> {
+        match self {
+            RustType::Path { segments, .. } => segments.last(),
+            _ => None,
+        }
+    }
+
+    ///Return types `Result<T, E>` and aliases like `io::Result<T>` become `Result<T, String>`
+    /// which flapigen turns into exceptions
+    pub(crate) fn collapse_result_error(self) -> RustType {
+        match self {
+            RustType::Path { segments, mut args }
+                if segments.last().is_some_and(|it| it == "Result")
+                    && (1..=2).contains(&args.len()) =>
+            {
+                args.truncate(1);
+                args.push(RustType::Path {
+                    segments: vec!["String".into()],
+                    args: vec![],
+                });
+                RustType::Path {
+                    segments: vec!["Result".into()],
+                    args,
+                }
+            }
+            other => other,
+        }
+    }
+
+    ///Rewrites the types flapigen has a special form for and checks they can be passed through it.
+    ///
+    /// `Self` becomes `self_type`, the type of the impl block, wherever it is ie `&Self`
+    /// or `Vec<Self>`.
+    ///
+    /// Annotated types lose their module path wherever they're defined
+    /// ie `model::Color` becomes `Color` and `Vec<model::Foo>` becomes `Vec<Foo>` since flapigen
+    /// only knows the class name.
+    ///
+    /// `Box<dyn Trait>` of an annotated trait is passed as a callback so the trait loses its
+    /// module path and lifetime bounds ie `Box<dyn listeners::OnClick + 'static>` becomes
+    /// `Box<dyn OnClick>`. So do `&dyn Trait` and `&mut dyn Trait`, which are only borrowed
+    /// for the call.
+    ///
+    /// Slices are resolved like `Vec`s ie `&[model::Foo]` becomes `&[Foo]`. Only slices of
+    /// primitive types like `&mut [u8]` may be mutable.
+    ///
+    /// `Arc<T>` and `Rc<T>` of an annotated type lose the module paths like `Vec`s.
+    ///
+    /// `HashMap` and `BTreeMap` lose their module path and their keys and values must be
+    /// primitive types or `String` ie `HashMap<String, i32>`. flapigen needs a
+    /// `foreign_typemap!` to convert them.
+    ///
+    /// flapigen has no fixed size arrays so they're passed in their dynamic form
+    /// ie `[u8; 32]` becomes `Vec<u8>` and `&mut [u8; 32]` becomes `&mut [u8]`.
+    /// See [`RustType::array_len`].
+    ///
+    /// flapigen has no tuples either so they're rejected unless the type map replaces them.
+    /// Nor lazy iterators, `impl Iterator<Item = T>` is rejected unless it's made a `Vec<T>`
+    /// by [`RustType::iterator_as_vec`].
+    ///
+    /// `Cow<str>` becomes `String` wherever it is ie `Option<Cow<'static, str>>` becomes
+    /// `Option<String>`, the foreign side always gets its own copy of the text.
+    /// `kinds` holds every annotated type
+    pub(crate) fn resolve(
+        &mut self,
+        self_type: &str,
+        kinds: &HashMap<String, Types>,
+    ) -> Result<(), String> {
+        self.replace_self(self_type);
+        let rendered = self.to_string();
+        self.replace_array();
+        self.replace_cow();
+        match self {
+            RustType::Path { segments, args }
+                if segments.last().is_some_and(|it| it == "Box")
+                    && matches!(args.as_slice(), [RustType::TraitObject(_)]) =>
+            {
+                if let [RustType::TraitObject(bounds)] = args.as_mut_slice() {
+                    if resolve_callback(bounds, kinds) {
+                        *segments = vec!["Box".into()];
+                    }
+                }
+                Ok(())
+            }
+            RustType::Reference { inner, .. } if matches!(**inner, RustType::TraitObject(_)) => {
+                if let RustType::TraitObject(bounds) = &mut **inner {
+                    resolve_callback(bounds, kinds);
+                }
+                Ok(())
+            }
+            RustType::Path { segments, args }
+                if segments.last().is_some_and(|it| it == "Arc" || it == "Rc")
+                    && args.len() == 1 =>
+            {
+                segments.drain(..segments.len() - 1);
+                match &mut args[0] {
+                    RustType::TraitObject(_) => {
+                        return Err(format!(
+                            "`{}` isn't supported, callbacks are passed as `Box<dyn Trait>`",
+                            rendered
+                        ))
+                    }
+                    RustType::Path { segments, .. } => {
+                        strip_known_path(segments, kinds);
+                    }
+                    _ => {}
+                }
+                args[0].resolve(self_type, kinds)
+            }
+            RustType::Path { segments, args }
+                if segments.last().is_some_and(|it| it == "Vec") && args.len() == 1 =>
+            {
+                *segments = vec!["Vec".into()];
+                args[0].resolve_element(&rendered, self_type, kinds)
+            }
+            RustType::Path { segments, args }
+                if segments
+                    .last()
+                    .is_some_and(|it| it == "HashMap" || it == "BTreeMap")
+                    && args.len() == 2 =>
+            {
+                segments.drain(..segments.len() - 1);
+                for (ty, role) in args.iter().zip(["key", "value"].iter()) {
+                    let is_supported = match ty {
+                        RustType::Path { args, .. } if args.is_empty() => ty
+                            .name()
+                            .is_some_and(|it| it == "String" || is_primitive(it)),
+                        _ => false,
+                    };
+                    if !is_supported {
+                        return Err(format!(
+                            "the {} type `{}` of `{}` isn't supported, only primitive types and \
+                             `String` are",
+                            role, ty, rendered
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            RustType::Reference { mutable, inner, .. } if matches!(**inner, RustType::Slice(_)) => {
+                if let RustType::Slice(element) = &mut **inner {
+                    //only primitives can be written back to the foreign array
+                    if *mutable && !element.name().is_some_and(|it| is_primitive(it)) {
+                        return Err(format!(
+                            "`{}` isn't supported, only slices of primitive types can be mutable",
+                            rendered
+                        ));
+                    }
+                    element.resolve_element(&rendered, self_type, kinds)?;
+                }
+                Ok(())
+            }
+            RustType::Iterator(_) => Err(format!(
+                "`{}` isn't supported since flapigen has no lazy iterators, return a `Vec` \
+                 instead or use `Generator::iterators_as_vec`",
+                rendered
+            )),
+            RustType::Tuple(types) if !types.is_empty() => Err(format!(
+                "tuples like `{}` aren't supported by flapigen, return a struct instead or map \
+                 the tuple to one with `Generator::with_type_map`",
+                rendered
+            )),
+            RustType::Path { segments, args } => {
+                strip_known_path(segments, kinds);
+                args.iter_mut()
+                    .try_for_each(|it| it.resolve(self_type, kinds))
+            }
+            RustType::Tuple(types) | RustType::TraitObject(types) => types
+                .iter_mut()
+                .try_for_each(|it| it.resolve(self_type, kinds)),
+            RustType::Reference { inner, .. }
+            | RustType::Slice(inner)
+            | RustType::Array(inner, _) => inner.resolve(self_type, kinds),
+            RustType::Lifetime(_) | RustType::Other(_) => Ok(()),
+        }
+    }
+
+    ///Resolves the element type of a `Vec` or slice. `container` is the whole type
+    fn resolve_element(
+        &mut self,
+        container: &str,
+        self_type: &str,
+        kinds: &HashMap<String, Types>,
+    ) -> Result<(), String> {
+        self.replace_self(self_type);
+        match self {
+            RustType::Path { segments, args } => {
+                let name = segments.last().cloned().unwrap_or_default();
+                if name == "Vec" {
+                    return Err(format!(
+                        "nested collections like `{}` aren't supported",
+                        container
+                    ));
+                }
+                let path = segments.join("::");
+                if kinds.get(&path).or_else(|| kinds.get(&name)) == Some(&Types::Trait) {
+                    return Err(format!(
+                        "`{}` isn't supported since `{}` is a callback",
+                        container, name
+                    ));
+                }
+                strip_known_path(segments, kinds);
+                args.iter_mut()
+                    .try_for_each(|it| it.resolve(self_type, kinds))
+            }
+            RustType::Reference { .. } => Err(format!(
+                "`{}` isn't supported, use a collection of owned values",
+                container
+            )),
+            RustType::Slice(_) | RustType::Array(..) => Err(format!(
+                "nested collections like `{}` aren't supported",
+                container
+            )),
+            other => other.resolve(self_type, kinds),
+        }
+    }
+
+    ///Drops the lifetimes wherever they are ie `&'a [u8]` becomes `&[u8]` and `Cow<'a, str>`
+    /// becomes `Cow<str>`, so a signature with named lifetimes is written like its elided form
+    pub(crate) fn strip_lifetimes(&mut self) {
+        match self {
+            RustType::Reference {
+                lifetime, inner, ..
+            } => {
+                *lifetime = None;
+                inner.strip_lifetimes();
+            }
+            RustType::Path { args, .. } => {
+                args.retain(|it| !matches!(it, RustType::Lifetime(_)));
+                args.iter_mut().for_each(|it| it.strip_lifetimes());
+            }
+            //lifetime bounds are only dropped from callbacks, see `RustType::resolve`
+            RustType::Tuple(types) | RustType::TraitObject(types) => {
+                types.iter_mut().for_each(|it| it.strip_lifetimes())
+            }
+            RustType::Slice(inner) | RustType::Array(inner, _) | RustType::Iterator(inner) => {
+                inner.strip_lifetimes()
+            }
+            RustType::Lifetime(_) | RustType::Other(_) => {}
+        }
+    }
+
+    ///`impl Iterator<Item = T>` becomes `Vec<T>` wherever it is
+    pub(crate) fn iterator_as_vec(&mut self) {
+        if let RustType::Iterator(item) = self {
+            let item = std::mem::replace(&mut **item, RustType::Other(String::new()));
+            *self = RustType::Path {
+                segments: vec!["Vec".into()],
+                args: vec![item],
+            }
+        }
+        match self {
+            RustType::Path { args: types, .. }
+            | RustType::Tuple(types)
+            | RustType::TraitObject(types) => types.iter_mut().for_each(|it| it.iterator_as_vec()),
+            RustType::Reference { inner, .. }
+            | RustType::Slice(inner)
+            | RustType::Array(inner, _)
+            | RustType::Iterator(inner) => inner.iterator_as_vec(),
+            RustType::Lifetime(_) | RustType::Other(_) => {}
+        }
+    }
+
+    ///`PathBuf` becomes `String` and `Path` becomes `str` wherever they are ie `&Path` becomes
+    /// `&str`. Only `std::path` types or types written without a path are replaced
+    pub(crate) fn paths_as_strings(&mut self) {
+        match self {
+            RustType::Path { segments, args } if args.is_empty() => {
+                let is_std = match segments.as_slice() {
+                    [_] => true,
+                    [std, path, _] => std == "std" && path == "path",
+                    _ => false,
+                };
+                let string = match segments.last().map(String::as_str) {
+                    Some("PathBuf") if is_std => "String",
+                    Some("Path") if is_std => "str",
+                    _ => return,
+                };
+                *segments = vec![string.to_string()];
+            }
+            RustType::Path { args: types, .. }
+            | RustType::Tuple(types)
+            | RustType::TraitObject(types) => types.iter_mut().for_each(|it| it.paths_as_strings()),
+            RustType::Reference { inner, .. }
+            | RustType::Slice(inner)
+            | RustType::Array(inner, _)
+            | RustType::Iterator(inner) => inner.paths_as_strings(),
+            RustType::Lifetime(_) | RustType::Other(_) => {}
+        }
+    }
+
+    ///`Cow<str>` becomes `String` wherever it is, whatever its lifetime
+    fn replace_cow(&mut self) {
+        if let RustType::Path { segments, args } = self {
+            let mut types = args
+                .iter()
+                .filter(|it| !matches!(it, RustType::Lifetime(_)));
+            let is_cow_str = segments.last().is_some_and(|it| it == "Cow")
+                && matches!(
+                    (types.next(), types.next()),
+                    (Some(ty), None) if ty.to_string() == "str"
+                );
+            if is_cow_str {
+                *segments = vec!["String".into()];
+                args.clear();
+            }
+        }
+        match self {
+            RustType::Path { args: types, .. }
+            | RustType::Tuple(types)
+            | RustType::TraitObject(types) => types.iter_mut().for_each(|it| it.replace_cow()),
+            RustType::Reference { inner, .. }
+            | RustType::Slice(inner)
+            | RustType::Array(inner, _)
+            | RustType::Iterator(inner) => inner.replace_cow(),
+            RustType::Lifetime(_) | RustType::Other(_) => {}
+        }
+    }
+
+    ///`[T; N]` becomes `Vec<T>` and `&[T; N]` becomes `&[T]`
+    fn replace_array(&mut self) {
+        match self {
+            RustType::Array(element, _) => {
+                let element = std::mem::replace(&mut **element, RustType::Other(String::new()));
+                *self = RustType::Path {
+                    segments: vec!["Vec".into()],
+                    args: vec![element],
+                }
+            }
+            RustType::Reference { inner, .. } => {
+                if let RustType::Array(element, _) = &mut **inner {
+                    let element = std::mem::replace(&mut **element, RustType::Other(String::new()));
+                    **inner = RustType::Slice(Box::new(element));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    ///Whether this is `&dyn Trait`, `&mut dyn Trait` or an `Option` of them where `Trait` is
+    /// an annotated trait. The callback is only borrowed for the call
+    pub(crate) fn is_borrowed_callback(&self, kinds: &HashMap<String, Types>) -> bool {
+        match self {
+            RustType::Reference { inner, .. } => match &**inner {
+                RustType::TraitObject(bounds) => bounds.iter().any(|it| is_callback(it, kinds)),
+                _ => false,
+            },
+            RustType::Path { segments, args }
+                if segments.last().is_some_and(|it| it == "Option") =>
+            {
+                matches!(args.as_slice(), [arg] if arg.is_borrowed_callback(kinds))
+            }
+            _ => false,
+        }
+    }
+
+    ///The length of a fixed size array, or a reference to one, ie `32` for `&mut [u8; 32]`.
+    /// It's lost when the array is resolved so it's documented instead
+    pub(crate) fn array_len(&self) -> Option<&str> {
+        match self {
+            RustType::Array(_, len) => Some(len),
+            RustType::Reference { inner, .. } => match &**inner {
+                RustType::Array(_, len) => Some(len),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    ///`Self` isn't known to flapigen so it becomes the type of the impl block
+    fn replace_self(&mut self, self_type: &str) {
+        if let RustType::Path { segments, args } = self {
+            if args.is_empty() && segments.len() == 1 && segments[0] == "Self" {
+                segments[0] = self_type.to_string();
+            }
+        }
+    }
+
+    ///Calls `f` with the segments of every path type in this type
+    pub(crate) fn paths_mut(
+        &mut self,
+        f: &mut dyn FnMut(&mut Vec<String>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        match self {
+            RustType::Path { segments, args } => {
+                f(segments)?;
+                args.iter_mut().try_for_each(|it| it.paths_mut(f))
+            }
+            RustType::Reference { inner, .. }
+            | RustType::Slice(inner)
+            | RustType::Array(inner, _)
+            | RustType::Iterator(inner) => inner.paths_mut(f),
+            RustType::Tuple(types) | RustType::TraitObject(types) => {
+                types.iter_mut().try_for_each(|it| it.paths_mut(f))
+            }
+            RustType::Lifetime(_) | RustType::Other(_) => Ok(()),
+        }
+    }
+
+    ///Paths of all the path types used in this type ie `model::Foo`.
+    /// Used to place the types a class depends on before it
+    pub(crate) fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_names(&mut names);
+        names
+    }
+
+    fn collect_names(&self, names: &mut Vec<String>) {
+        match self {
+            RustType::Path { segments, args } => {
+                names.push(segments.join("::"));
+                args.iter().for_each(|it| it.collect_names(names));
+            }
+            RustType::Reference { inner, .. }
+            | RustType::Slice(inner)
+            | RustType::Array(inner, _)
+            | RustType::Iterator(inner) => inner.collect_names(names),
+            RustType::Tuple(types) | RustType::TraitObject(types) => {
+                types.iter().for_each(|it| it.collect_names(names))
+            }
+            RustType::Lifetime(_) | RustType::Other(_) => {}
+        }
+    }
+}
+
+///The item type of `impl Iterator<Item = T>`, or of the other iterator traits
+fn iterator_item(bounds: &Punctuated<TypeParamBound, Token![+]>) -> Option<&syn::Type> {
+    bounds.iter().find_map(|bound| {
+        let segment = match bound {
+            TypeParamBound::Trait(bound) => bound.path.segments.last()?,
+            TypeParamBound::Lifetime(_) => return None,
+        };
+        let is_iterator = ["Iterator", "ExactSizeIterator", "DoubleEndedIterator"]
+            .iter()
+            .any(|it| segment.ident == it);
+        match &segment.arguments {
+            PathArguments::AngleBracketed(args) if is_iterator => {
+                args.args.iter().find_map(|arg| match arg {
+                    GenericArgument::Binding(binding) if binding.ident == "Item" => {
+                        Some(&binding.ty)
+                    }
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    })
+}
+
+///Removes the module path of an annotated type unless it's known by its path
+fn strip_known_path(segments: &mut Vec<String>, kinds: &HashMap<String, Types>) {
+    let is_known = |name: &String| kinds.contains_key(name);
+    if !is_known(&segments.join("::")) && segments.last().is_some_and(is_known) {
+        segments.drain(..segments.len() - 1);
+    }
+}
+
+///Whether `bound` is an annotated trait
+fn is_callback(bound: &RustType, kinds: &HashMap<String, Types>) -> bool {
+    bound
+        .name()
+        .is_some_and(|name| kinds.get(name) == Some(&Types::Trait))
+}
+
+///When the trait object with `bounds` is a callback, its traits lose their module path and
+/// lifetime bounds are dropped. Returns whether it's a callback
+fn resolve_callback(bounds: &mut Vec<RustType>, kinds: &HashMap<String, Types>) -> bool {
+    if !bounds.iter().any(|it| is_callback(it, kinds)) {
+        return false;
+    }
+    bounds.retain(|it| !matches!(it, RustType::Lifetime(_)));
+    for bound in bounds {
+        if let RustType::Path { segments, .. } = bound {
+            segments.drain(..segments.len() - 1);
+        }
+    }
+    true
+}
+
+///Integer, float and `bool` types
+pub fn is_primitive(name: &str) -> bool {
+    matches!(
+        name,
+        "i8" | "i16"
+            | "i32"
+            | "i64"
+            | "isize"
+            | "u8"
+            | "u16"
+            | "u32"
+            | "u64"
+            | "usize"
+            | "f32"
+            | "f64"
+            | "bool"
+    )
+}
+
+///Substitutes types in the generated signatures ie `Uuid` with `String` when there's a
+/// `foreign_typemap!` for it. Keys are matched against the whole type, then against the type
+/// without its module path so `Uuid` also matches `uuid::Uuid`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TypeMap {
+    types: HashMap<String, RustType>,
+    ///types the target language has no counterpart for, with why
+    unsupported: HashMap<String, String>,
+}
+
+impl TypeMap {
+    ///The mappings rifgen knows for `language`. flapigen converts `SystemTime` to
+    /// `java.util.Date` but there's no counterpart in the other languages so it's unsupported
+    /// there
+    pub fn builtin(language: Language) -> TypeMap {
+        let mut map = TypeMap::default();
+        let path = |name: &str| RustType::Path {
+            segments: vec![name.to_string()],
+            args: vec![],
+        };
+        if matches!(language, Language::Cpp | Language::Python) {
+            map.reject(
+                path("SystemTime"),
+                format!(
+                    "`SystemTime` has no counterpart in {:?}, map it ie to the milliseconds since \
+                     the epoch with `Generator::with_type_map`",
+                    language
+                ),
+            );
+        }
+        map
+    }
+
+    ///Mappings added later override earlier ones
+    pub fn insert(&mut self, rust_type: RustType, foreign_type: RustType) {
+        let key = rust_type.to_string();
+        self.unsupported.remove(&key);
+        self.types.insert(key, foreign_type);
+    }
+
+    ///Marks `rust_type` as unsupported until a mapping for it is inserted
+    pub fn reject(&mut self, rust_type: RustType, reason: String) {
+        let key = rust_type.to_string();
+        self.types.remove(&key);
+        self.unsupported.insert(key, reason);
+    }
+
+    ///Adds the mappings of `other`, which override those of `self`
+    pub fn extend(&mut self, other: TypeMap) {
+        for (key, reason) in other.unsupported {
+            self.types.remove(&key);
+            self.unsupported.insert(key, reason);
+        }
+        for (key, ty) in other.types {
+            self.unsupported.remove(&key);
+            self.types.insert(key, ty);
+        }
+    }
+
+    ///Fails with the reason of the first unsupported type used in `ty`, which is matched with
+    /// and without its module path like the mappings
+    pub fn check(&self, ty: &RustType) -> Result<(), String> {
+        for name in ty.names() {
+            let short = name.rsplit("::").next().unwrap_or_default();
+            if let Some(reason) = self
+                .unsupported
+                .get(&name)
+                .or_else(|| self.unsupported.get(short))
+            {
+                return Err(reason.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    ///`rust_type => foreign_type` for every mapping, sorted
+    pub fn entries(&self) -> impl Iterator<Item = String> {
+        let mut entries = self
+            .types
+            .iter()
+            .map(|(key, value)| format!("{} => {}", key, value))
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries.into_iter()
+    }
+
+    pub fn apply(&self, ty: &mut RustType) {
+        if let Some(val) = self.get(ty) {
+            *ty = val.clone();
+            return;
+        }
+        match ty {
+            RustType::Path { args: types, .. }
+            | RustType::Tuple(types)
+            | RustType::TraitObject(types) => types.iter_mut().for_each(|it| self.apply(it)),
+            RustType::Reference { inner, .. }
+            | RustType::Slice(inner)
+            | RustType::Array(inner, _)
+            | RustType::Iterator(inner) => self.apply(inner),
+            RustType::Lifetime(_) | RustType::Other(_) => {}
+        }
+    }
+
+    pub fn get(&self, ty: &RustType) -> Option<&RustType> {
+        self.types.get(&ty.to_string()).or_else(|| match ty {
+            RustType::Path { segments, args } if segments.len() > 1 => {
+                let short = RustType::Path {
+                    segments: segments[segments.len() - 1..].to_vec(),
+                    args: args.clone(),
+                };
+                self.types.get(&short.to_string())
+            }
+            _ => None,
+        })
+    }
+}
+
+impl Display for RustType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn join(types: &[RustType], separator: &str) -> String {
+            types
+                .iter()
+                .map(|it| it.to_string())
+                .collect::<Vec<_>>()
+                .join(separator)
+        }
+        match self {
+            RustType::Path { segments, args } => {
+                write!(f, "{}", segments.join("::"))?;
+                if !args.is_empty() {
+                    write!(f, "<{}>", join(args, ", "))?;
+                }
+                Ok(())
+            }
+            RustType::Reference {
+                lifetime,
+                mutable,
+                inner,
+            } => {
+                write!(f, "&")?;
+                if let Some(lifetime) = lifetime {
+                    write!(f, "{} ", lifetime)?;
+                }
+                if *mutable {
+                    write!(f, "mut ")?;
+                }
+                write!(f, "{}", inner)
+            }
+            RustType::Slice(inner) => write!(f, "[{}]", inner),
+            RustType::Array(inner, len) => write!(f, "[{}; {}]", inner, len),
+            RustType::Tuple(types) if types.len() == 1 => write!(f, "({},)", types[0]),
+            RustType::Tuple(types) => write!(f, "({})", join(types, ", ")),
+            RustType::TraitObject(bounds) => write!(f, "dyn {}", join(bounds, " + ")),
+            RustType::Iterator(item) => write!(f, "impl Iterator<Item = {}>", item),
+            RustType::Lifetime(val) | RustType::Other(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maps::{RustType, TypeMap};
+    use crate::Language;
+    use std::collections::HashMap;
+
+    fn parse(ty: &str) -> RustType {
+        RustType::from_syn(&syn::parse_str(ty).unwrap())
+    }
+
+    #[test]
+    fn result_error_is_collapsed() {
+        for (ty, expected) in [
+            ("Result<(), Error>", "Result<(), String>"),
+            ("Result<Vec<u8>, _>", "Result<Vec<u8>, String>"),
+            ("std::result::Result<i32, String>", "Result<i32, String>"),
+            ("io::Result<u32>", "Result<u32, String>"),
+            ("Option<Foo>", "Option<Foo>"),
+        ] {
+            assert_eq!(parse(ty).collapse_result_error().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn types_are_written_back() {
+        for ty in [
+            "i32",
+            "Option<String>",
+            "Option<Foo>",
+            "&'a mut [u8]",
+            "Box<dyn Callback + 'static>",
+            "HashMap<String, Vec<i32>>",
+            "[u8; 32]",
+            "(f64, f64)",
+        ] {
+            assert_eq!(parse(ty).to_string(), ty);
+        }
+        assert_eq!(
+            parse("Option<Vec<Foo>>").names(),
+            vec!["Option", "Vec", "Foo"]
+        );
+    }
+
+    #[test]
+    fn iterators() {
+        let mut ty = parse("impl Iterator<Item = Foo> + Send");
+        assert_eq!(ty.to_string(), "impl Iterator<Item = Foo>");
+        assert!(ty.resolve("Foo", &HashMap::new()).is_err());
+        ty.iterator_as_vec();
+        assert_eq!(ty.to_string(), "Vec<Foo>");
+        assert!(matches!(parse("impl Display"), RustType::Other(_)));
+    }
+
+    #[test]
+    fn string_wrappers() {
+        for (ty, expected) in [
+            ("Cow<str>", "String"),
+            ("Cow<'static, str>", "String"),
+            ("Option<std::borrow::Cow<'a, str>>", "Option<String>"),
+            ("Vec<Cow<str>>", "Vec<String>"),
+            ("Cow<[u8]>", "Cow<[u8]>"),
+        ] {
+            let mut ty = parse(ty);
+            ty.resolve("Foo", &HashMap::new()).unwrap();
+            assert_eq!(ty.to_string(), expected);
+        }
+        for (ty, expected) in [
+            ("PathBuf", "String"),
+            ("&std::path::Path", "&str"),
+            ("Option<&Path>", "Option<&str>"),
+            ("camino::Utf8PathBuf", "camino::Utf8PathBuf"),
+        ] {
+            let mut ty = parse(ty);
+            ty.paths_as_strings();
+            assert_eq!(ty.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn builtin_mappings() {
+        let mut java = TypeMap::builtin(Language::Java);
+        let mut ty = parse("Option<std::time::Duration>");
+        java.apply(&mut ty);
+        assert_eq!(ty.to_string(), "Option<std::time::Duration>");
+        let mut durations = TypeMap::default();
+        durations.insert(parse("Duration"), parse("u64"));
+        java.extend(durations);
+        java.apply(&mut ty);
+        assert_eq!(ty.to_string(), "Option<u64>");
+        assert!(java.check(&parse("SystemTime")).is_ok());
+
+        let mut cpp = TypeMap::builtin(Language::Cpp);
+        assert!(cpp.check(&parse("Vec<std::time::SystemTime>")).is_err());
+        let mut overrides = TypeMap::default();
+        overrides.insert(parse("SystemTime"), parse("i64"));
+        cpp.extend(overrides);
+        assert!(cpp.check(&parse("SystemTime")).is_ok());
+    }
+}
@@ -0,0 +1,33 @@
+//! The annotated types as they're written to the interface file, see [`crate::Generator::collect`].
+//!
+//! Types are already resolved, ie the type map is applied, aliases are expanded and `Vec`s of
+//! annotated types lose their module path. Doc comments aren't converted yet and methods aren't
+//! in the order they're written in
+pub use crate::enums::Types;
+pub use crate::maps::RustType;
+pub use crate::types_structs::{Enum, ItemInfo, MethodInfo, Struct, Trait};
+
+/// Everything that's written to the interface file
+#[derive(Debug, Clone, Default)]
+pub struct Interface {
+    /// The structs with annotated methods, in name order
+    pub classes: Vec<Struct>,
+    /// In name order
+    pub enums: Vec<Enum>,
+    /// The callbacks, in name order
+    pub traits: Vec<Trait>,
+}
+
+/// How much would be generated, see [`crate::Generator::dry_run_summary`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenSummary {
+    pub classes: usize,
+    /// Of the classes and callbacks, including constructors and the getters of constants
+    pub methods: usize,
+    pub enums: usize,
+    /// The callbacks
+    pub traits: usize,
+    /// What each skipped unsupported item is and why it's skipped, sorted since files are
+    /// scanned in parallel
+    pub skipped: Vec<String>,
+}
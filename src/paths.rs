@@ -0,0 +1,302 @@
+use crate::enums::Types;
+use crate::maps::{RustType, TypeMap};
+use inflector::Inflector;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+///The annotated types and the names they're known by in the interface file.
+/// A type is known by its name unless annotated types in different modules share it,
+/// then each is known by its path ie `net::Config` and `ui::Config`
+#[derive(Debug, Default)]
+pub struct KnownTypes {
+    ///path of every struct, enum and trait in the source files, annotated or not
+    defined: HashSet<Vec<String>>,
+    ///names shared by types in different modules
+    ambiguous: HashSet<String>,
+    ///the kind of every annotated type by the name it's known by
+    pub kinds: HashMap<String, Types>,
+    ///`type` aliases without generic parameters by their name
+    aliases: TypeMap,
+    ///names of aliases defined more than once with different types
+    conflicting_aliases: HashSet<String>,
+    ///the name of each type re-exported under another name by its definition
+    renamed: HashMap<Vec<String>, String>,
+    ///the path each name imported with `use` stands for by the module it's imported in
+    imports: HashMap<(Vec<String>, String), Vec<String>>,
+}
+
+impl KnownTypes {
+    ///`used` are the paths of the annotated types and the types of annotated impl blocks
+    pub fn new(
+        defined: impl Iterator<Item = Vec<String>>,
+        used: impl Iterator<Item = Vec<String>>,
+    ) -> KnownTypes {
+        let mut known = KnownTypes {
+            defined: defined.collect(),
+            ..KnownTypes::default()
+        };
+        let mut paths: HashMap<String, BTreeSet<Vec<String>>> = HashMap::new();
+        for path in used {
+            let path = known.definition(path);
+            if let Some(name) = path.last() {
+                paths.entry(name.to_string()).or_default().insert(path);
+            }
+        }
+        known.ambiguous = paths
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(name, _)| name)
+            .collect();
+        known
+    }
+
+    ///Records `pub use target as name;`. A type re-exported under another name is known by
+    /// that name, and written with it wherever it's used. When a type is re-exported under
+    /// several names the first is kept and returned as the error
+    pub fn add_reexport(&mut self, target: Vec<String>, name: &str) -> Result<(), String> {
+        let target = self.definition(target);
+        if !self.defined.contains(&target) {
+            return Ok(());
+        }
+        match self.renamed.get(&target) {
+            Some(previous) if previous != name => Err(previous.to_string()),
+            Some(_) => Ok(()),
+            None => {
+                let original = target.last().cloned().unwrap_or_default();
+                let renamed = RustType::Path {
+                    segments: vec![name.to_string()],
+                    args: vec![],
+                };
+                self.add_alias(&original, &renamed);
+                self.renamed.insert(target, name.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    ///Records `use target as name;` in `module`. Only imports of types defined in the source
+    /// files, or of the modules they're in, are used
+    pub fn add_import(&mut self, module: &[String], target: &[String], name: &str) {
+        self.imports
+            .insert((module.to_vec(), name.to_string()), target.to_vec());
+    }
+
+    ///Adds `type name = ty;`
+    pub fn add_alias(&mut self, name: &str, ty: &RustType) {
+        let alias = RustType::Path {
+            segments: vec![name.to_string()],
+            args: vec![],
+        };
+        match self.aliases.get(&alias) {
+            Some(previous) if previous != ty => {
+                self.conflicting_aliases.insert(name.to_string());
+            }
+            _ => self.aliases.insert(alias, ty.clone()),
+        }
+    }
+
+    ///Replaces the aliases in `ty` with the types they stand for, following aliases of aliases
+    pub fn expand_aliases(&self, ty: &mut RustType) -> Result<(), String> {
+        ty.paths_mut(&mut |segments| match segments.last() {
+            Some(name) if self.conflicting_aliases.contains(name) => Err(format!(
+                "the type alias `{}` is defined more than once with different types",
+                name
+            )),
+            _ => Ok(()),
+        })?;
+        //bounded in case aliases refer to each other
+        for _ in 0..8 {
+            let previous = ty.to_string();
+            self.aliases.apply(ty);
+            if ty.to_string() == previous {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    ///The path the trait written as `path` in `module` is imported with ie
+    /// `crate::shapes::Drawable`. Traits from other crates keep their path, `None` when it's
+    /// not known where the trait is
+    pub fn trait_import(&self, module: &[String], path: &str) -> Option<String> {
+        let segments = path.split("::").map(String::from).collect::<Vec<_>>();
+        let definition = self.definition(qualify(module, &segments));
+        if self.defined.contains(&definition) {
+            return Some(format!("crate::{}", definition.join("::")));
+        }
+        let is_relative = matches!(segments[0].as_str(), "crate" | "self" | "super");
+        (segments.len() > 1 && !is_relative).then(|| path.to_string())
+    }
+
+    ///Where `path` is defined. An impl block may use a type imported from another module so
+    /// when nothing is defined at `path` but a single type has its name, it's that type
+    pub fn definition(&self, path: Vec<String>) -> Vec<String> {
+        if self.defined.contains(&path) {
+            return path;
+        }
+        let mut same_name = self.defined.iter().filter(|it| it.last() == path.last());
+        match (same_name.next(), same_name.next()) {
+            (Some(definition), None) => definition.to_vec(),
+            _ => path,
+        }
+    }
+
+    ///The name the type at `path` is known by
+    pub fn key(&self, path: &[String]) -> String {
+        if let Some(name) = self.renamed.get(&self.definition(path.to_vec())) {
+            return name.to_string();
+        }
+        let name = path.last().cloned().unwrap_or_default();
+        if self.ambiguous.contains(&name) {
+            self.definition(path.to_vec()).join("::")
+        } else {
+            name
+        }
+    }
+
+    ///Writes the types in `ty` that share their name with other annotated types as the path
+    /// they're known by. `module` is where `ty` is used
+    pub fn qualify_type(&self, module: &[String], ty: &mut RustType) -> Result<(), String> {
+        ty.paths_mut(&mut |segments| {
+            //`Renamed` of `use crate::model::Other as Renamed;` is `Other`
+            let imported = segments
+                .first()
+                .and_then(|first| self.imports.get(&(module.to_vec(), first.to_string())))
+                .map(|target| [&target[..], &segments[1..]].concat())
+                .filter(|path| self.defined.contains(path));
+            if let Some(path) = imported {
+                *segments = self.key(&path).split("::").map(String::from).collect();
+                return Ok(());
+            }
+            match segments.last() {
+                Some(name) if self.ambiguous.contains(name) => {}
+                _ => return Ok(()),
+            }
+            let key = self.key(&qualify(module, segments));
+            if !self.kinds.contains_key(&key) {
+                return Err(format!(
+                    "`{}` could be any of the annotated types with that name, write its path \
+                     ie `crate::module::{}`",
+                    segments.join("::"),
+                    segments.last().unwrap()
+                ));
+            }
+            *segments = key.split("::").map(String::from).collect();
+            Ok(())
+        })
+    }
+}
+
+///The foreign name of a type known by its path ie `NetConfig` for `net::Config`
+pub fn foreign_name(key: &str) -> Option<String> {
+    key.contains("::")
+        .then(|| key.split("::").map(|it| it.to_pascal_case()).collect())
+}
+
+///The foreign name of the class `class` instantiated with `types` ie `WrapperString` for
+/// `Wrapper<String>` and `CacheVecI32` for `Cache<Vec<i32>>`
+pub fn instantiated_name(class: &str, types: &[RustType]) -> String {
+    let mut name = class.to_string();
+    for ty in types {
+        let ty = ty.to_string();
+        name.extend(
+            ty.split(|it: char| !it.is_alphanumeric())
+                .filter(|it| !it.is_empty())
+                .map(|it| it.to_pascal_case()),
+        );
+    }
+    name
+}
+
+///The path from the crate root of `path` used in `module`, where `crate`, `self` and `super`
+/// are replaced ie `super::Foo` in `a::b` is `a::Foo`
+pub fn qualify(module: &[String], path: &[String]) -> Vec<String> {
+    let mut result = module.to_vec();
+    for (i, segment) in path.iter().enumerate() {
+        match segment.as_str() {
+            "crate" if i == 0 => result.clear(),
+            "self" => {}
+            "super" => {
+                result.pop();
+            }
+            _ => result.push(segment.to_string()),
+        }
+    }
+    result
+}
+
+///The module of `file`, taking `starting_point` as the root of the crate
+/// ie `net/mod.rs`, `net.rs` and `net.rs.tmpl` are all `net` while `lib.rs` is the root
+pub fn file_module(starting_point: &Path, file: &Path) -> Vec<String> {
+    let relative = file.strip_prefix(starting_point).unwrap_or(file);
+    let mut module = relative
+        .iter()
+        .map(|it| it.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    //module names can't contain dots so everything after the first is the extension
+    if let Some(name) = module.last_mut() {
+        name.truncate(name.find('.').unwrap_or(name.len()));
+    }
+    let is_root = match module.as_slice() {
+        [name] => name == "lib" || name == "main",
+        _ => module.last().is_some_and(|it| it == "mod"),
+    };
+    if is_root {
+        module.pop();
+    }
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enums::Types;
+    use crate::maps::RustType;
+    use crate::paths::{file_module, foreign_name, instantiated_name, qualify, KnownTypes};
+    use std::path::Path;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split("::").map(String::from).collect()
+    }
+
+    #[test]
+    fn modules() {
+        let src = Path::new("src");
+        assert!(file_module(src, Path::new("src/lib.rs")).is_empty());
+        assert_eq!(file_module(src, Path::new("src/net.rs")), path("net"));
+        assert_eq!(file_module(src, Path::new("src/net/mod.rs")), path("net"));
+        assert_eq!(file_module(src, Path::new("src/net.rs.tmpl")), path("net"));
+        assert_eq!(
+            file_module(src, Path::new("src/net/tcp.rs")),
+            path("net::tcp")
+        );
+        assert_eq!(qualify(&path("a::b"), &path("super::Foo")), path("a::Foo"));
+        assert_eq!(
+            qualify(&path("a::b"), &path("crate::c::Foo")),
+            path("c::Foo")
+        );
+        assert_eq!(qualify(&path("a"), &path("self::Foo")), path("a::Foo"));
+    }
+
+    #[test]
+    fn shared_names() {
+        let defined = ["net::Config", "ui::Config", "model::Foo", "Bar"].map(path);
+        let used = ["net::Config", "ui::Config", "ffi::Foo", "Bar"].map(path);
+        let mut known = KnownTypes::new(defined.iter().cloned(), used.iter().cloned());
+        for name in ["net::Config", "ui::Config", "Foo", "Bar"] {
+            known.kinds.insert(name.to_string(), Types::Struct);
+        }
+        assert_eq!(known.key(&path("ffi::Foo")), "Foo");
+        assert_eq!(known.key(&path("net::Config")), "net::Config");
+        assert_eq!(foreign_name("net::Config").as_deref(), Some("NetConfig"));
+        assert_eq!(foreign_name("Foo"), None);
+        let types =
+            ["String", "Vec<i32>"].map(|it| RustType::from_syn(&syn::parse_str(it).unwrap()));
+        assert_eq!(instantiated_name("Wrapper", &types), "WrapperStringVecI32");
+
+        let mut ty = RustType::from_syn(&syn::parse_str("Vec<Config>").unwrap());
+        known.qualify_type(&path("ui"), &mut ty).unwrap();
+        assert_eq!(ty.to_string(), "Vec<ui::Config>");
+        let mut ty = RustType::from_syn(&syn::parse_str("Config").unwrap());
+        assert!(known.qualify_type(&path("ffi"), &mut ty).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! The JSON list of generated symbols written with [`crate::Generator::emit_manifest`]
+use crate::model::{Interface, ItemInfo};
+use crate::text_formatter::variant_case;
+use crate::{EnumCase, TypeCases};
+use std::path::Path;
+
+///Bumped whenever a field is renamed or removed. Adding fields keeps the version
+pub const VERSION: u32 = 1;
+
+///Describes every class, callback and enum with its methods or variants, their foreign names
+/// and the files they're declared in. Paths are `null` when they aren't known
+pub(crate) fn to_json(interface: &Interface, type_case: TypeCases, enum_case: EnumCase) -> String {
+    let mut json = format!("{{\n  \"version\": {},\n", VERSION);
+    let types = [
+        (
+            "classes",
+            interface
+                .classes
+                .iter()
+                .map(|it| (&it.name, it.class_name(type_case), &it.file, &it.extras))
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "callbacks",
+            interface
+                .traits
+                .iter()
+                .map(|it| (&it.name, it.class_name(type_case), &it.file, &it.extras))
+                .collect(),
+        ),
+        (
+            "enums",
+            interface
+                .enums
+                .iter()
+                .map(|it| (&it.name, it.class_name(type_case), &it.file, &it.extras))
+                .collect(),
+        ),
+    ];
+    for (i, (kind, types)) in types.iter().enumerate() {
+        json.push_str(&format!("  \"{}\": [", kind));
+        for (j, (name, foreign_name, file, extras)) in types.iter().enumerate() {
+            json.push_str(if j == 0 { "\n" } else { ",\n" });
+            json.push_str(&format!(
+                "    {{\n      \"name\": {},\n      \"foreign_name\": {},\n      \"file\": {},\n",
+                string(name),
+                string(foreign_name),
+                path(file.as_deref())
+            ));
+            let member = if *kind == "enums" {
+                "variants"
+            } else {
+                "methods"
+            };
+            json.push_str(&format!("      \"{}\": [", member));
+            for (k, extra) in extras.iter().enumerate() {
+                json.push_str(if k == 0 { "\n" } else { ",\n" });
+                json.push_str(&format!("        {}", item(extra, type_case, enum_case)));
+            }
+            json.push_str(if extras.is_empty() {
+                "]\n"
+            } else {
+                "\n      ]\n"
+            });
+            json.push_str("    }");
+        }
+        json.push_str(if types.is_empty() { "]" } else { "\n  ]" });
+        json.push_str(if i + 1 < 3 { ",\n" } else { "\n" });
+    }
+    json.push_str("}\n");
+    json
+}
+
+///A method or variant on a single line
+fn item(item: &ItemInfo, type_case: TypeCases, enum_case: EnumCase) -> String {
+    let (name, foreign_name) = match &item.method_info {
+        Some(info) => (info.name().to_string(), info.foreign_name(type_case)),
+        None => (
+            item.signature.to_string(),
+            variant_case(&item.signature, enum_case),
+        ),
+    };
+    format!(
+        "{{\"name\": {}, \"foreign_name\": {}, \"constructor\": {}, \"file\": {}}}",
+        string(&name),
+        string(&foreign_name),
+        item.is_constructor,
+        path(item.file.as_deref())
+    )
+}
+
+fn path(path: Option<&Path>) -> String {
+    match path {
+        Some(path) => string(&path.display().to_string()),
+        None => "null".to_string(),
+    }
+}
+
+///`text` as a JSON string, quoted and escaped
+fn string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => result.push_str("\\r"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symbols::string;
+
+    #[test]
+    fn strings_are_escaped() {
+        assert_eq!(string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+}
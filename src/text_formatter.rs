@@ -1,19 +1,31 @@
 use crate::enums::{Delimiters, NewLineState};
+use crate::{EnumCase, Indent, Language, LineEnding, TypeCases};
+use inflector::Inflector;
 
 ///For formatting the output file
 pub(crate) struct StringFormatter {
     pub string_container: String,
     current_number_of_tabs: u16,
     delimiters: Vec<Delimiters>,
+    /// what a single level of indentation is written as
+    indent: String,
 }
 
 impl StringFormatter {
     ///For a new struct the current number of tabs added should be 0
-    pub fn new(string_container: String, current_number_of_tabs: u16) -> StringFormatter {
+    pub fn new(
+        string_container: String,
+        current_number_of_tabs: u16,
+        indent: Indent,
+    ) -> StringFormatter {
         StringFormatter {
             string_container,
             current_number_of_tabs,
             delimiters: Vec::with_capacity(2),
+            indent: match indent {
+                Indent::Tabs => "\t".to_string(),
+                Indent::Spaces(count) => " ".repeat(count),
+            },
         }
     }
 
@@ -28,7 +40,7 @@ impl StringFormatter {
             NewLineState::ShiftLeft => self.current_number_of_tabs -= 1,
         };
         //take effect
-        let new_tabs = "\t".repeat(self.current_number_of_tabs as usize);
+        let new_tabs = self.indent.repeat(self.current_number_of_tabs as usize);
         self.string_container.push('\n');
         self.string_container.push_str(&new_tabs);
     }
@@ -98,8 +110,10 @@ impl StringFormatter {
 
     pub fn close_all_delimiters(&mut self) {
         while let Some(delimiter) = self.delimiters.pop() {
-            let tab = self.string_container.pop().unwrap(); //remove the tab
-            assert_eq!(tab, '\t');
+            //remove the indentation
+            assert!(self.string_container.ends_with(&self.indent));
+            let len = self.string_container.len() - self.indent.len();
+            self.string_container.truncate(len);
             self.add_text_and_then_line(
                 vec![match delimiter {
                     Delimiters::Parenthesis => ")",
@@ -118,14 +132,289 @@ impl StringFormatter {
     }
 }
 
+///Writes the line breaks of `text`, which are `\n`, as `line_ending`
+pub(crate) fn with_line_ending(text: String, line_ending: LineEnding) -> String {
+    match line_ending {
+        LineEnding::Lf => text,
+        LineEnding::CrLf => text.replace('\n', "\r\n"),
+    }
+}
+
+///Converts the name of a method or parameter to the chosen case
+/// ie `set_field` becomes `setField` with `TypeCases::CamelCase`
+pub(crate) fn method_case(name: &str, type_case: TypeCases) -> String {
+    //raw identifiers are converted without their `r#`
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    match type_case {
+        TypeCases::Default => name.to_string(),
+        TypeCases::CamelCase => name.to_camel_case(),
+        TypeCases::PascalCase => name.to_pascal_case(),
+        TypeCases::SnakeCase => name.to_snake_case(),
+    }
+}
+
+///`name` as a rust identifier, which is raw when it's a keyword ie `r#type`
+pub(crate) fn rust_ident(name: String) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let",
+        "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+        "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+        "virtual", "where", "while", "yield",
+    ];
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+///Converts the name of a class, enum or callback to the chosen case.
+/// Type names are upper camel case with both `TypeCases::CamelCase` and `TypeCases::PascalCase`
+pub(crate) fn type_name_case(name: &str, type_case: TypeCases) -> String {
+    match type_case {
+        TypeCases::CamelCase | TypeCases::PascalCase => name.to_pascal_case(),
+        TypeCases::Default | TypeCases::SnakeCase => name.to_string(),
+    }
+}
+
+///Converts the name of an enum variant to the chosen case
+pub(crate) fn variant_case(name: &str, enum_case: EnumCase) -> String {
+    match enum_case {
+        EnumCase::Unchanged => name.to_string(),
+        EnumCase::ScreamingSnake => name.to_screaming_snake_case(),
+        EnumCase::Pascal => name.to_pascal_case(),
+    }
+}
+
+///The doc comment line marking an item as deprecated, with the note of `#[deprecated]` when
+/// there's one. It's a block tag so it goes after the rest of the doc comment
+pub(crate) fn deprecation_doc(note: &str, language: Language) -> String {
+    let tag = match language {
+        Language::Java | Language::Kotlin => "@deprecated",
+        Language::Cpp => "\\deprecated",
+        Language::Python => "Deprecated.",
+    };
+    if note.is_empty() {
+        tag.to_string()
+    } else {
+        format!("{} {}", tag, note)
+    }
+}
+
+///Rewrites the markdown of a line of a doc comment to what the documentation tool of `language`
+/// understands.
+/// For java, intra-doc links become `{@link X}`, code spans `<code>...</code>` and `<`, `>` and `&`
+/// are escaped. For C++, intra-doc links become Doxygen's `\ref X` and `<`, `>` and `&` outside
+/// code spans are escaped. KDoc is markdown so for kotlin only the intra-doc links change
+/// ie ``[`Foo::bar`]`` becomes `[Foo.bar]`. Python docs are left as they are
+pub(crate) fn convert_doc(doc: &str, language: Language) -> String {
+    let java = match language {
+        Language::Java => true,
+        Language::Cpp => false,
+        Language::Kotlin => return kotlin_doc(doc),
+        Language::Python => return doc.to_string(),
+    };
+    let escape = |c: char| match (c, java) {
+        ('<', true) => Some("&lt;"),
+        ('>', true) => Some("&gt;"),
+        ('&', true) => Some("&amp;"),
+        ('<', false) => Some("\\<"),
+        ('>', false) => Some("\\>"),
+        ('&', false) => Some("\\&"),
+        _ => None,
+    };
+    let mut result = String::with_capacity(doc.len());
+    let mut rest = doc;
+    while let Some(c) = rest.chars().next() {
+        if let Some((target, len)) = intra_doc_link(rest) {
+            if java {
+                result.push_str(&format!("{{@link {}}}", java_link(target)));
+            } else {
+                result.push_str(&format!("\\ref {}", target));
+            }
+            rest = &rest[len..];
+            continue;
+        }
+        if let Some(end) = rest.strip_prefix('`').and_then(|it| it.find('`')) {
+            let code = &rest[1..end + 1];
+            if java {
+                result.push_str("<code>");
+                for c in code.chars() {
+                    match escape(c) {
+                        Some(val) => result.push_str(val),
+                        None => result.push(c),
+                    }
+                }
+                result.push_str("</code>");
+            } else {
+                //Doxygen understands markdown code spans
+                result.push_str(&rest[..end + 2]);
+            }
+            rest = &rest[end + 2..];
+            continue;
+        }
+        match escape(c) {
+            Some(val) => result.push_str(val),
+            None => result.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+fn kotlin_doc(doc: &str) -> String {
+    let mut result = String::with_capacity(doc.len());
+    let mut rest = doc;
+    while let Some(c) = rest.chars().next() {
+        if let Some((target, len)) = intra_doc_link(rest) {
+            let path = target
+                .strip_suffix("()")
+                .unwrap_or(target)
+                .split("::")
+                .filter(|it| !matches!(*it, "crate" | "self" | "super"))
+                .collect::<Vec<_>>();
+            result.push_str(&format!("[{}]", path.join(".")));
+            rest = &rest[len..];
+            continue;
+        }
+        //links in code spans are left alone
+        if let Some(end) = rest.strip_prefix('`').and_then(|it| it.find('`')) {
+            result.push_str(&rest[..end + 2]);
+            rest = &rest[end + 2..];
+            continue;
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+///The target of an intra-doc link ie `Foo::bar` for ``[`Foo::bar`]`` and the length of the link
+/// when `text` starts with one
+fn intra_doc_link(text: &str) -> Option<(&str, usize)> {
+    let rest = text.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    //markdown links ie `[text](url)` are left alone
+    if rest[end + 1..].starts_with('(') || rest[end + 1..].starts_with('[') {
+        return None;
+    }
+    let inner = &rest[..end];
+    let target = inner
+        .strip_prefix('`')
+        .and_then(|it| it.strip_suffix('`'))
+        .unwrap_or(inner);
+    let path = target.strip_suffix("()").unwrap_or(target);
+    let is_path = !path.is_empty()
+        && path.split("::").all(|segment| {
+            segment
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+                && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+        });
+    if is_path {
+        Some((target, end + 2))
+    } else {
+        None
+    }
+}
+
+///`Foo::bar` becomes `Foo#bar` and `module::Foo` becomes `module.Foo`
+fn java_link(target: &str) -> String {
+    let (path, parentheses) = match target.strip_suffix("()") {
+        Some(path) => (path, "()"),
+        None => (target, ""),
+    };
+    let segments = path
+        .split("::")
+        .filter(|it| !matches!(*it, "crate" | "self" | "super"))
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        [owner @ .., member]
+            if owner
+                .last()
+                .is_some_and(|it| it.starts_with(char::is_uppercase)) =>
+        {
+            format!("{}#{}{}", owner.join("."), member, parentheses)
+        }
+        _ => format!("{}{}", segments.join("."), parentheses),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::enums::{Delimiters, NewLineState};
-    use crate::text_formatter::StringFormatter;
+    use crate::text_formatter::{
+        convert_doc, method_case, type_name_case, variant_case, StringFormatter,
+    };
+    use crate::{EnumCase, Indent, Language, TypeCases};
+
+    #[test]
+    fn doc_conversion() {
+        let doc = " See [`Foo::bar`] for details. Returns `Vec<u8>` if a < b && [Color].";
+        assert_eq!(
+            convert_doc(doc, Language::Java),
+            " See {@link Foo#bar} for details. Returns <code>Vec&lt;u8&gt;</code> if a &lt; b &amp;&amp; {@link Color}."
+        );
+        assert_eq!(
+            convert_doc(doc, Language::Cpp),
+            " See \\ref Foo::bar for details. Returns `Vec<u8>` if a \\< b \\&\\& \\ref Color."
+        );
+        assert_eq!(convert_doc(doc, Language::Python), doc);
+        assert_eq!(
+            convert_doc(doc, Language::Kotlin),
+            " See [Foo.bar] for details. Returns `Vec<u8>` if a < b && [Color]."
+        );
+        assert_eq!(
+            convert_doc(
+                "[`crate::model::load()`] [link](https://a.b) [1, 2] `open",
+                Language::Java
+            ),
+            "{@link model.load()} [link](https://a.b) [1, 2] `open"
+        );
+    }
+
+    #[test]
+    fn variant_names() {
+        assert_eq!(
+            variant_case("DarkRed", EnumCase::ScreamingSnake),
+            "DARK_RED"
+        );
+        assert_eq!(
+            variant_case("LightBlue", EnumCase::ScreamingSnake),
+            "LIGHT_BLUE"
+        );
+        assert_eq!(variant_case("DARK_RED", EnumCase::Pascal), "DarkRed");
+        assert_eq!(variant_case("DarkRed", EnumCase::Unchanged), "DarkRed");
+    }
+
+    #[test]
+    fn camel_case_names() {
+        assert_eq!(method_case("set_field", TypeCases::CamelCase), "setField");
+        assert_eq!(
+            method_case("new_from_str", TypeCases::CamelCase),
+            "newFromStr"
+        );
+        assert_eq!(method_case("setField", TypeCases::CamelCase), "setField");
+        assert_eq!(type_name_case("my_class", TypeCases::CamelCase), "MyClass");
+        assert_eq!(type_name_case("MyClass", TypeCases::CamelCase), "MyClass");
+    }
+
+    #[test]
+    fn pascal_case_names() {
+        assert_eq!(method_case("set_field", TypeCases::PascalCase), "SetField");
+        assert_eq!(
+            method_case("new_from_str", TypeCases::PascalCase),
+            "NewFromStr"
+        );
+        assert_eq!(method_case("setField", TypeCases::PascalCase), "SetField");
+        assert_eq!(type_name_case("MyClass", TypeCases::PascalCase), "MyClass");
+    }
 
     #[test]
     fn testing_various_states() {
-        let mut format = StringFormatter::new(String::new(), 0);
+        let mut format = StringFormatter::new(String::new(), 0, Indent::Tabs);
         format.add_text_and_then_line(vec!["fn ", "main", "{"], NewLineState::ShiftRight);
         format.add_text_and_then_line(vec!["let", "x", "= 15 {"], NewLineState::ShiftRight);
         format.add_text_and_then_line(vec!["32"], NewLineState::Current);
@@ -137,7 +426,7 @@ mod tests {
 
     #[test]
     fn testing_delimiters() {
-        let mut format = StringFormatter::new(String::new(), 0);
+        let mut format = StringFormatter::new(String::new(), 0, Indent::Tabs);
         format.add_text_delimiter_then_line(
             vec!["fn ", "main"],
             Delimiters::Bracket,
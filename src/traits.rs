@@ -1,3 +1,32 @@
+use crate::maps::{is_primitive, RustType};
+
+///Checks the return type of a callback method. The foreign implementation hands its value back
+/// to rust so only what flapigen converts by value can be returned ie `i32`, `String` or
+/// `Option<f64>`
+pub fn check_callback_output(output: &RustType) -> Result<(), String> {
+    let is_value = |ty: &RustType| match ty {
+        RustType::Path { args, .. } if args.is_empty() => ty
+            .name()
+            .is_some_and(|name| name == "String" || is_primitive(name)),
+        _ => false,
+    };
+    let supported = match output {
+        RustType::Path { segments, args } if segments.last().is_some_and(|it| it == "Option") => {
+            matches!(args.as_slice(), [inner] if is_value(inner))
+        }
+        RustType::Tuple(types) => types.is_empty(),
+        ty => is_value(ty),
+    };
+    if supported {
+        Ok(())
+    } else {
+        Err(format!(
+            "callbacks can't return `{}`, only primitive types, `String` and `Option`s of them",
+            output
+        ))
+    }
+}
+
 /*
 todo!()
 //trait to give a more dsl feel
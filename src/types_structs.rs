@@ -1,61 +1,182 @@
 use crate::enums::{Delimiters, NewLineState, Types};
+use crate::error::Location;
 use crate::generator_lib::{F_CALLBACK, F_CLASS, F_ENUM};
-use crate::text_formatter::StringFormatter;
-use crate::TypeCases;
+use crate::maps::RustType;
+use crate::text_formatter::{
+    method_case, rust_ident, type_name_case, variant_case, StringFormatter,
+};
+use crate::{EnumCase, Indent, TypeCases};
 use derive_new::new;
-use inflector::Inflector;
-use std::iter::Chain;
-use std::slice::Iter;
+use std::path::PathBuf;
 
-//todo: avoid static mut
-pub(crate) static mut TYPE_CASE: TypeCases = TypeCases::Default;
-
-#[derive(Debug, new)]
+///A method or enum variant
+#[derive(Debug, Clone, new)]
 pub struct ItemInfo {
     ///doc for the method or variant
     pub docs: Vec<String>,
-    /// could be an enum variant or a method
+    /// name of the enum variant or the method
     pub signature: String,
     /// item type
     pub is_constructor: bool,
     ///method name to use with only methods
     ///No enums included
     pub method_info: Option<MethodInfo>,
+    ///value of an enum variant when the enum has explicit discriminants
+    #[new(default)]
+    pub discriminant: Option<i128>,
+    ///the trait of the impl block the method is in ie `crate::shapes::Drawable`, which the
+    /// interface file imports since flapigen calls the method as `Foo::draw`
+    #[new(default)]
+    pub implemented_trait: Option<String>,
+    ///the file the method or variant is declared in
+    #[new(default)]
+    pub file: Option<PathBuf>,
+    ///where the method is declared in `file`
+    #[new(default)]
+    pub location: Option<Location>,
+    ///the note of `#[deprecated]`, empty when it has none
+    #[new(default)]
+    pub deprecated: Option<String>,
+    ///the supertrait a callback method is declared in, which it's written with
+    /// ie `Observer` for `notify = Observer::notify(&self);`
+    #[new(default)]
+    pub inherited_from: Option<String>,
+    ///the languages of `#[generate_interface(only = "java")]`, every language when empty
+    #[new(default)]
+    pub only: Vec<String>,
+    ///the module of a free function, which it's called with instead of the class ie `net` for
+    /// `fn net::init();` and empty at the crate root
+    #[new(default)]
+    pub function_path: Option<String>,
 }
-#[derive(Debug, new)]
-
+///The signature of a method
+#[derive(Debug, Clone, new)]
 pub struct MethodInfo {
     name: String,
-    types_in_method: Vec<String>,
-    return_types: Vec<String>,
+    /// `&self`, `&mut self` or `self`
+    receiver: Option<String>,
+    /// name and type of each parameter apart from the receiver
+    args: Vec<(String, RustType)>,
+    output: Option<RustType>,
+    /// `#[generate_interface(name = "...")]` which is used as is instead of the converted name
+    #[new(default)]
+    pub rename: Option<String>,
+    /// the associated constant returned by this getter, which flapigen generates itself
+    #[new(default)]
+    pub constant: Option<String>,
 }
 
 impl MethodInfo {
-    pub fn all_types(&self) -> Chain<Iter<'_, String>, Iter<'_, String>> {
-        self.types_in_method.iter().chain(self.return_types.iter())
+    ///The name of the method in the source code
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    ///`&self`, `&mut self` or `self`, or what's written ie `self: Arc<Self>`
+    pub fn receiver(&self) -> Option<&str> {
+        self.receiver.as_deref()
+    }
+
+    ///The name and type of each parameter apart from the receiver
+    pub fn args(&self) -> &[(String, RustType)] {
+        &self.args
+    }
+
+    ///Methods without a receiver are static methods unless they're constructors
+    pub fn is_static(&self) -> bool {
+        self.receiver.is_none()
+    }
+
+    ///The name of the method in the foreign language
+    pub fn foreign_name(&self, type_case: TypeCases) -> String {
+        match &self.rename {
+            Some(name) => name.to_string(),
+            None => method_case(&self.name, type_case),
+        }
+    }
+
+    ///All the types used in the parameters and the return type
+    pub(crate) fn all_types(&self) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|(_, ty)| ty)
+            .chain(self.output.iter())
+            .flat_map(|it| it.names())
+            .collect()
+    }
+
+    ///Leaves the parameters called any of `names` out of the interface
+    pub(crate) fn skip_args(&mut self, names: &[String]) {
+        self.args.retain(|(name, _)| !names.contains(name));
+    }
+
+    ///The return type, `None` for `()`
+    pub fn output(&self) -> Option<&RustType> {
+        self.output.as_ref()
+    }
+
+    ///The parameter and return types
+    pub(crate) fn types_mut(&mut self) -> impl Iterator<Item = &mut RustType> {
+        self.args
+            .iter_mut()
+            .map(|(_, ty)| ty)
+            .chain(self.output.iter_mut())
+    }
+
+    ///Notes the length of the fixed size arrays which are passed as slices or `Vec`s
+    /// ie "`buf` must hold 32 elements"
+    pub(crate) fn array_docs(&self) -> Vec<String> {
+        self.args
+            .iter()
+            .filter_map(|(name, ty)| {
+                ty.array_len()
+                    .map(|len| format!("`{}` must hold {} elements", name, len))
+            })
+            .chain(
+                self.output
+                    .iter()
+                    .filter_map(|it| it.array_len())
+                    .map(|len| format!("Returns {} elements", len)),
+            )
+            .collect()
+    }
+
+    ///The signature as written after `Type::` in the interface file
+    /// ie `set_field(&mut self, v: i32) -> bool`
+    pub fn signature(&self, type_case: TypeCases) -> String {
+        let args =
+            self.receiver
+                .iter()
+                .cloned()
+                .chain(self.args.iter().map(|(name, ty)| {
+                    format!("{}: {}", rust_ident(method_case(name, type_case)), ty)
+                }))
+                .collect::<Vec<_>>()
+                .join(", ");
+        match &self.output {
+            Some(output) => format!("{}({}) -> {}", self.name, args, output),
+            None => format!("{}({})", self.name, args),
+        }
     }
 }
 
 impl ItemInfo {
     ///Creates a new `ItemInfo` which is a method
-    pub fn new_method(
-        signature: String,
+    pub(crate) fn new_method(
         docs: Vec<String>,
-        method_name: String,
         is_constructor: bool,
-        types_in_method: Vec<String>,
-        return_types: Vec<String>,
+        method_info: MethodInfo,
     ) -> ItemInfo {
         ItemInfo::new(
             docs,
-            signature,
+            method_info.name.to_string(),
             is_constructor,
-            Some(MethodInfo::new(method_name, types_in_method, return_types)),
+            Some(method_info),
         )
     }
 
     ///Creates a new `ItemInfo` which is an enum
-    pub fn new_enum(signature: String, docs: Vec<String>) -> ItemInfo {
+    pub(crate) fn new_enum(signature: String, docs: Vec<String>) -> ItemInfo {
         ItemInfo::new(docs, signature, false, None)
     }
 }
@@ -64,10 +185,9 @@ impl ItemInfo {
 #[doc(hidden)]
 macro_rules! add_doc {
     ($expr:expr,$formatter:expr) => {{
-        $expr
-            .docs
-            .iter()
-            .for_each(|it| $formatter.add_text_and_then_line(vec![it], NewLineState::Current));
+        $expr.docs.iter().for_each(|it| {
+            $formatter.add_text_and_then_line(vec!["///", it], NewLineState::Current)
+        });
     }};
 }
 
@@ -78,7 +198,7 @@ macro_rules! add_doc {
 macro_rules! gen_structs {
     ($($name:ident),*) => {
         $(
-            #[derive(Debug,new)]
+            #[derive(Debug,Clone,new)]
             pub struct $name {
                 /// Name of struct, Trait or Enum
                 pub name: String,
@@ -87,21 +207,50 @@ macro_rules! gen_structs {
                 pub docs: Vec<String>,
                 /// the methods or variants with this type
                 pub extras: Vec<ItemInfo>,
+                /// used instead of `name`, which is then a path, when types in different
+                /// modules have the same name ie `NetConfig` for `net::Config`
+                #[new(default)]
+                pub foreign_name: Option<String>,
+                /// the rust type when it isn't `name` ie `Wrapper<String>` for a generic
+                /// struct instantiated with `String` or `crate::net::Config` with
+                /// [`crate::Generator::crate_root`]
+                #[new(default)]
+                pub self_type: Option<String>,
+                /// the file the type is defined in, or that of an impl block when the
+                /// definition wasn't found
+                #[new(default)]
+                pub file: Option<PathBuf>,
+                /// the note of `#[deprecated]`, empty when it has none
+                #[new(default)]
+                pub deprecated: Option<String>,
             }
 
             impl $name {
-                pub fn generate_interface(&mut self) -> String {
-                    let mut formatter = StringFormatter::new(String::with_capacity(1024), 0);
+                pub(crate) fn class_name(&self, type_case: TypeCases) -> String {
+                    type_name_case(self.foreign_name.as_ref().unwrap_or(&self.name), type_case)
+                }
+
+                ///The rust type and the path its methods or variants are written with
+                /// ie `Wrapper<String>` and `Wrapper::<String>`
+                fn rust_paths(&self) -> (String, String) {
+                    match &self.self_type {
+                        Some(ty) => (ty.to_string(), ty.replacen('<', "::<", 1)),
+                        None => (self.name.to_string(), self.name.to_string()),
+                    }
+                }
+
+                pub(crate) fn generate_interface(&mut self, type_case: TypeCases, enum_case: EnumCase, indent: Indent) -> String {
+                    let mut formatter = StringFormatter::new(String::with_capacity(1024), 0, indent);
                     match self.type_ {
-                        Types::Struct => self.format_struct(&mut formatter),
-                        Types::Trait => self.format_trait(&mut formatter),
-                        Types::Enum => {self.format_enum(&mut formatter)}
+                        Types::Struct => self.format_struct(&mut formatter, type_case),
+                        Types::Trait => self.format_trait(&mut formatter, type_case),
+                        Types::Enum => {self.format_enum(&mut formatter, type_case, enum_case)}
                     }
                     formatter.close_all_delimiters();
                     formatter.string_container
                 }
 
-                fn format_struct(&mut self, formatter: &mut StringFormatter) {
+                fn format_struct(&mut self, formatter: &mut StringFormatter, type_case: TypeCases) {
                     //Case where the struct has constructors
                     let constructors = {
                          let mut result = vec![];
@@ -117,6 +266,7 @@ macro_rules! gen_structs {
                          result
                      };
                     let any_is_constructor = !constructors.is_empty();
+                    let (self_type, path) = self.rust_paths();
                     formatter.add_text_delimiter_then_line(
                         vec![F_CLASS],
                         Delimiters::Parenthesis,
@@ -125,48 +275,71 @@ macro_rules! gen_structs {
                     //Add the doc comment associated with this struct
                     add_doc!(self, formatter);
                     formatter.add_text_delimiter_then_line(
-                            vec!["class ", &self.name],
+                            vec!["class ", &self.class_name(type_case)],
                             Delimiters::Bracket,
                             NewLineState::ShiftRight,
                     );
 
                     if any_is_constructor {
-                        formatter.add_text_and_colon(vec!["self_type ",&self.name]);
+                        formatter.add_text_and_colon(vec!["self_type ",&self_type]);
                         for constructor in constructors {
                             //add doc comment
-                            formatter.add_text_and_then_line(
-                                constructor.docs.iter().map(|it| it.as_str()).collect(),
-                                NewLineState::Current,
-                            );
+                            add_doc!(&constructor, formatter);
                             formatter.add_text_and_colon(vec![
                                 "constructor ",
-                                &self.name,
+                                &path,
                                 "::",
-                                &constructor.signature,
+                                &constructor.method_info.as_ref().unwrap().signature(type_case),
                             ])
                         }
                     }
 
                     for extra in &self.extras {
                         add_doc!(&extra, formatter);
-                        let alias = unsafe {
-                            match TYPE_CASE {
-                                TypeCases::Default => String::new(),
-                                TypeCases::CamelCase => (&extra.method_info).as_ref().unwrap().name.to_camel_case(),
-                                TypeCases::SnakeCase => (&extra.method_info).as_ref().unwrap().name.to_snake_case(),
+                        let method_info = extra.method_info.as_ref().unwrap();
+                        if let Some(constant) = &method_info.constant {
+                            let name = method_info.rename.as_ref().unwrap_or(&method_info.name);
+                            //flapigen generates the getter of a constant from its body
+                            let output = method_info.output.as_ref().map(|it| it.to_string());
+                            formatter.add_text_and_then_line(
+                                vec![
+                                    "fn ",
+                                    name,
+                                    "() -> ",
+                                    &output.unwrap_or_default(),
+                                    " { ",
+                                    &path,
+                                    "::",
+                                    constant,
+                                    " }",
+                                ],
+                                NewLineState::Current,
+                            );
+                            continue;
+                        }
+                        //raw identifiers are aliased to drop their `r#`
+                        let alias = match (type_case, &method_info.rename) {
+                            (TypeCases::Default, None) if !method_info.name.starts_with("r#") => {
+                                String::new()
                             }
+                            _ => format!("; alias {}", method_info.foreign_name(type_case)),
                         };
-                        let alias = if alias.is_empty() {
-                            alias
-                        } else {
-                            format!("; alias {}", alias)
+                        let path = match &extra.function_path {
+                            Some(module) if module.is_empty() => String::new(),
+                            Some(module) => format!("{}::", module),
+                            None => format!("{}::", path),
                         };
-                        formatter.add_text_and_colon(vec!["fn ", &self.name, "::", &extra.signature, &alias])
+                        formatter.add_text_and_colon(vec![
+                            "fn ",
+                            &path,
+                            &method_info.signature(type_case),
+                            &alias,
+                        ])
                     }
                 }
 
-                fn format_trait(&mut self, formatter: &mut StringFormatter) {
-                    //println!("trait called");
+                fn format_trait(&mut self, formatter: &mut StringFormatter, type_case: TypeCases) {
+                    let (self_type, path) = self.rust_paths();
                     formatter.add_text_delimiter_then_line(
                         vec![F_CALLBACK],
                         Delimiters::Parenthesis,
@@ -174,26 +347,26 @@ macro_rules! gen_structs {
                     );
                     add_doc!(self, formatter);
                     formatter.add_text_delimiter_then_line(
-                        vec!["callback ", &self.name],
+                        vec!["callback ", &self.class_name(type_case)],
                         Delimiters::Bracket,
                         NewLineState::ShiftRight,
                     );
-                    formatter.add_text_and_colon(vec!["self_type ",&self.name]);
+                    formatter.add_text_and_colon(vec!["self_type ",&self_type]);
                     for extra in &self.extras {
                         add_doc!(extra, formatter);
-                        let mut name = extra.method_info.as_ref().unwrap().name.to_string();
-                        name = unsafe {
-                            match TYPE_CASE {
-                                TypeCases::Default => name,
-                                TypeCases::CamelCase => (&name).to_camel_case(),
-                                TypeCases::SnakeCase => (&name).to_snake_case(),
-                            }
-                        };
-                        formatter.add_text_and_colon(vec![&name, " = ", &self.name, "::", &extra.signature])
+                        let method_info = extra.method_info.as_ref().unwrap();
+                        formatter.add_text_and_colon(vec![
+                            &method_info.foreign_name(type_case),
+                            " = ",
+                            extra.inherited_from.as_deref().unwrap_or(&path),
+                            "::",
+                            &method_info.signature(type_case),
+                        ])
                     }
                 }
 
-                fn format_enum(&mut self, formatter: &mut StringFormatter) {
+                fn format_enum(&mut self, formatter: &mut StringFormatter, type_case: TypeCases, enum_case: EnumCase) {
+                    let (_, path) = self.rust_paths();
                     formatter.add_text_delimiter_then_line(
                         vec![F_ENUM],
                         Delimiters::Parenthesis,
@@ -201,16 +374,23 @@ macro_rules! gen_structs {
                     );
                     add_doc!(self, formatter);
                     formatter.add_text_delimiter_then_line(
-                        vec!["enum ", &self.name],
+                        vec!["enum ", &self.class_name(type_case)],
                         Delimiters::Bracket,
                         NewLineState::ShiftRight,
                     );
                     for extra in &self.extras {
                         add_doc!(extra, formatter);
+                        //flapigen numbers the variants in order so the rust value is documented
+                        if let Some(value) = extra.discriminant {
+                            formatter.add_text_and_then_line(
+                                vec!["///Discriminant: ", &value.to_string()],
+                                NewLineState::Current,
+                            );
+                        }
                         formatter.add_text_and_comma(vec![
-                            &extra.signature,
+                            &variant_case(&extra.signature, enum_case),
                             " = ",
-                            &self.name,
+                            &path,
                             "::",
                             &extra.signature,
                         ])
@@ -0,0 +1,94 @@
+use crate::{GenError, Generator, Result, Settings};
+use notify::{RecursiveMode, Watcher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+///How long the folders must be quiet before generating again
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+pub fn watch(generator: Generator, interface_file_path: &Path) -> Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+    for folder in &generator.settings().scr_folders {
+        watcher
+            .watch(folder, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+    }
+    regenerate(&generator, interface_file_path, &[]);
+    while let Ok(event) = receiver.recv() {
+        let mut changed = Vec::new();
+        add_changes(
+            event,
+            generator.settings(),
+            interface_file_path,
+            &mut changed,
+        );
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            add_changes(
+                event,
+                generator.settings(),
+                interface_file_path,
+                &mut changed,
+            );
+        }
+        if !changed.is_empty() {
+            regenerate(&generator, interface_file_path, &changed);
+        }
+    }
+    Ok(())
+}
+
+///Keeps the files of `event` that would be scanned. The interface file is skipped in case it's in
+/// a source folder
+fn add_changes(
+    event: notify::Result<notify::Event>,
+    settings: &Settings,
+    interface_file_path: &Path,
+    changed: &mut Vec<PathBuf>,
+) {
+    let event = match event {
+        Ok(event) if !event.kind.is_access() => event,
+        Ok(_) => return,
+        Err(err) => return eprintln!("rifgen: {}", err),
+    };
+    for path in event.paths {
+        let folder = settings
+            .scr_folders
+            .iter()
+            .find(|it| path.starts_with(it))
+            .map_or(path.as_path(), |it| it.as_path());
+        let is_source = settings.has_extension(&path) && !settings.is_excluded(folder, &path);
+        if is_source && !path.ends_with(interface_file_path) && !changed.contains(&path) {
+            changed.push(path);
+        }
+    }
+}
+
+fn regenerate(generator: &Generator, interface_file_path: &Path, changed: &[PathBuf]) {
+    let start = Instant::now();
+    let cause = match changed {
+        [] => String::new(),
+        [path] => format!(" after {} changed", path.display()),
+        paths => format!(" after {} files changed", paths.len()),
+    };
+    match generator.clone().generate_interface(interface_file_path) {
+        Ok(()) => println!(
+            "rifgen: generated {}{} in {}ms",
+            interface_file_path.display(),
+            cause,
+            start.elapsed().as_millis()
+        ),
+        Err(err) => eprintln!(
+            "rifgen: {}{}: {}",
+            interface_file_path.display(),
+            cause,
+            err
+        ),
+    }
+}
+
+fn watch_error(err: notify::Error) -> GenError {
+    GenError::Io(io::Error::other(err))
+}